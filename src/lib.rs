@@ -1 +1,4 @@
+//! Ray tracing core: geometry, materials, scenes and integrators. Windowing
+//! and UI glue (macroquad/egui) is only pulled in by the `app` feature.
 
+pub mod raytracer;
@@ -1,55 +1,55 @@
 #![allow(dead_code, unused_variables)]
 
-use std::{f32::INFINITY, rc::Rc};
-
 use egui_macroquad::egui;
 use macroquad::{prelude::*, rand::RandomRange};
-mod raytracer;
-
-use raytracer::{
+use rust_raytracing_from_scratch::raytracer::{
+    self,
     camera::Camera,
-    geometry::reflect,
-    hit_info::{HitInfo, Hittable},
-    material::{LambertianMaterial, MaterialKind},
+    conductor::Conductor,
+    film::{Film, Filter},
+    geometry::Point,
+    integrator::{render_pixel, MaxDepth},
+    material::MaterialKind,
+    math::{Float, Vec3},
+    plane::Plane,
+    radiance::Rgb,
     ray::Ray,
+    sampler::{spp_heatmap, AdaptiveSampling, SamplingMode},
     sphere::Sphere,
+    texture::Texture,
     world::World,
 };
 
-fn color_lerp(a: Color, b: Color, t: f32) -> Color {
-    let rr = a.r * (1. - t) + b.r * t;
-    let gg = a.g * (1. - t) + b.g * t;
-    let bb = a.b * (1. - t) + b.b * t;
-    let aa = a.a * (1. - t) + b.a * t;
-    Color::new(rr, gg, bb, aa)
-}
+const DEFAULT_WIDTH: u16 = 1600;
+const DEFAULT_HEIGHT: u16 = 900;
 
 fn window_conf() -> Conf {
     Conf {
         window_title: "3D Engine".to_owned(),
         // fullscreen: true,
-        window_width: 1600,
-        window_height: 900,
+        window_width: DEFAULT_WIDTH as i32,
+        window_height: DEFAULT_HEIGHT as i32,
+        window_resizable: true,
         ..Default::default()
     }
 }
 
-fn ray_color(ray: &raytracer::ray::Ray) -> Color {
+fn ray_color(ray: &raytracer::ray::Ray) -> Rgb {
     let unit_direction = ray.direction.normalize();
     let t = 0.5 * (unit_direction.y + 1.0);
-    let white = Color::new(1.0, 1.0, 1.0, 1.0);
-    let blue = Color::new(0.5, 0.7, 1.0, 1.0);
+    let white = Rgb::new(1.0, 1.0, 1.0);
+    let blue = Rgb::new(0.5, 0.7, 1.0);
 
-    color_lerp(white, blue, t)
+    white.lerp(blue, t)
 }
 
 struct NoIntersectionError;
 
-fn hit_sphere(center: Vec3, radius: f32, ray: &Ray) -> Result<f32, NoIntersectionError> {
+fn hit_sphere(center: Point, radius: Float, ray: &Ray) -> Result<Float, NoIntersectionError> {
     let oc = ray.origin - center;
-    let a = ray.direction.dot(ray.direction);
-    let b = 2.0 * oc.dot(ray.direction);
-    let c = oc.dot(oc) - radius * radius;
+    let a = ray.direction.dot(*ray.direction);
+    let b = 2.0 * oc.dot(*ray.direction);
+    let c = oc.dot(*oc) - radius * radius;
     let discriminant = b * b - 4.0 * a * c;
     if discriminant < 0. {
         Err(NoIntersectionError)
@@ -58,143 +58,98 @@ fn hit_sphere(center: Vec3, radius: f32, ray: &Ray) -> Result<f32, NoIntersectio
     }
 }
 
-fn random() -> f32 {
+fn random() -> Float {
     RandomRange::gen_range(0., 1.)
 }
 
 fn random_in_unit_sphere() -> Vec3 {
-    let mut p = Vec3::new(0., 0., 0.);
     loop {
-        p = 2. * Vec3::new(random(), random(), random()) - Vec3::new(1., 1., 1.);
+        let p = 2. * Vec3::new(random(), random(), random()) - Vec3::new(1., 1., 1.);
         if p.length_squared() < 1. {
-            break;
+            return p;
         }
     }
-    p
 }
 
-fn get_ray_color(ray: &Ray, world: &World, depth: u8) -> Color {
-    if depth == 0 {
-        return Color::new(0., 0., 0., 1.);
-    }
+#[allow(clippy::too_many_arguments)]
+fn render_update(
+    camera: &Camera,
+    world: &World,
+    sampling: SamplingMode,
+    max_depth: MaxDepth,
+    filter: Filter,
+    spectral: bool,
+    screen_image: &mut Image,
+    heatmap_image: &mut Image,
+) {
+    let width = screen_image.width();
+    let height = screen_image.height();
+    let mut film = Film::new(width, height, filter);
+
+    for x in 0..width {
+        for y in 0..height {
+            let stats = render_pixel(
+                camera, world, x, y, width, height, sampling, max_depth, spectral, &mut film,
+            );
 
-    if let Some(hit) = world.hit(ray, 0.001, INFINITY) {
-        let (attenuation, ray, ok) = hit.material.scatter(ray, &hit);
-        if ok {
-            let c = get_ray_color(&ray, world, depth - 1);
-            return Color::new(
-                c.r * attenuation.r,
-                c.g * attenuation.g,
-                c.b * attenuation.b,
-                1.,
+            heatmap_image.set_pixel(
+                x as u32,
+                y as u32,
+                spp_heatmap(stats.count(), sampling.max_samples()).into(),
             );
-        } else {
-            return Color::new(0., 0., 0., 1.);
         }
-        // match hit.material {
-        //     MaterialKind::Lambertian { albedo } => {
-        //         let reflected = reflect(ray.direction.normalize(), hit.normal);
-        //         let scattered = Ray::new(hit.p, reflected);
-        //         let attenuation = albedo;
-        //         let col = get_ray_color(&scattered, world, depth - 1);
-        //         return Color::new(
-        //             col.r * attenuation.r,
-        //             col.g * attenuation.g,
-        //             col.b * attenuation.b,
-        //             1.,
-        //         );
-        //     }
-        //     MaterialKind::Metal { albedo } => {
-        //         let reflected = reflect(ray.direction.normalize(), hit.normal);
-        //         let scattered = Ray::new(hit.p, reflected);
-        //         let attenuation = albedo;
-        //         let col = get_ray_color(&scattered, world, depth - 1);
-        //         return Color::new(
-        //             col.r * attenuation.r,
-        //             col.g * attenuation.g,
-        //             col.b * attenuation.b,
-        //             1.,
-        //         );
-        //     }
-        // }
-
-        // let col = get_ray_color(&Ray::new(hit.p, target - hit.p), world, depth - 1);
-        // return Color::new(col.r * 0.5, col.g * 0.5, col.b * 0.5, 1.);
     }
 
-    let unit_direction = ray.direction.normalize();
-    let t = 0.5 * (unit_direction.y + 1.0);
-    let white = Color::new(1.0, 1.0, 1.0, 1.0);
-    let blue = Color::new(0.5, 0.7, 1.0, 1.0);
-
-    color_lerp(white, blue, t)
-}
-
-fn render_update(camera: &Camera, world: &World, screen_image: &mut Image) {
-    for x in 0..1600 {
-        for y in 0..900 {
-            let u = x as f32 / 1599.;
-            let v = y as f32 / 899.;
-            let dir = camera.lower_left_corner + u * camera.horizontal + v * camera.vertical
-                - camera.origin;
-
-            let ray = Ray::from_camera(camera, u, v);
-
-            let hit = world.hit(&ray, 0.001, 1000.);
-
-            if let Some(hit) = hit {
-                // println!("hit: {:?}", hit);
-                // let normal = hit.normal; //(hit_point - Point::new(0., 0., -1.)).normalize();
-                // let c = Color::new(
-                //     0.5 * (normal.x + 1.),
-                //     0.5 * (normal.y + 1.),
-                //     0.5 * (normal.z + 1.),
-                //     1.,
-                // );
-                let mut c = Color::new(0., 0., 0., 1.);
-                for i in 0..10 {
-                    let u = (x as f32 + random()) / 1599.;
-                    let v = (y as f32 + random()) / 899.;
-                    let ray = Ray::from_camera(camera, u, v);
-                    let col = get_ray_color(&ray, world, 50);
-                    c.r += col.r;
-                    c.g += col.g;
-                    c.b += col.b;
-                }
-                c.r /= 10.;
-                c.g /= 10.;
-                c.b /= 10.;
-                // let c = get_ray_color(&ray, world, 50);
-                // screen_image.set_pixel(x, y, c);
-                /* !TODO: ANTIALIASING
-                let c = Color::default();
-
-                for i in 0..3 {
-                    let u = u + rand::gen_range(0., 1.) as f32 / 1599.;
-                    let v = v + rand::gen_range(0., 1.) as f32 / 899.;
-                    let ray = Ray::from_camera(&camera, u, v);
-                    Color::new(
-                        0.5 * (normal.x + 1.),
-                        0.5 * (normal.y + 1.),
-                        0.5 * (normal.z + 1.),
-                        1.,
-                    );
-                } */
-                screen_image.set_pixel(x, y, c);
-            } else {
-                let c = ray_color(&ray);
-                screen_image.set_pixel(x, y, c);
-            }
+    for x in 0..width {
+        for y in 0..height {
+            screen_image.set_pixel(x as u32, y as u32, film.pixel(x, y).to_color().into());
         }
     }
 }
 
+/// Draws `texture` as large as possible inside the window, preserving its aspect ratio.
+fn draw_fitted(texture: Texture2D) {
+    let scale = (screen_width() / texture.width()).min(screen_height() / texture.height());
+    let size = vec2(texture.width() * scale, texture.height() * scale);
+    let x = (screen_width() - size.x) / 2.;
+    let y = (screen_height() - size.y) / 2.;
+
+    draw_texture_ex(
+        texture,
+        x,
+        y,
+        WHITE,
+        DrawTextureParams {
+            dest_size: Some(size),
+            ..Default::default()
+        },
+    );
+}
+
 #[macroquad::main(window_conf)]
 async fn main() {
-    let mut camera = Camera::default();
-
-    let mut screen_image = Image::gen_image_color(1600, 900, RED);
-    let screen_texture = Texture2D::from_image(&screen_image);
+    let mut resolution = (DEFAULT_WIDTH, DEFAULT_HEIGHT);
+    let mut selected_resolution = resolution;
+
+    let mut camera = Camera::with_resolution(
+        Vec3::default(),
+        resolution.0 as usize,
+        resolution.1 as usize,
+        1.,
+    );
+
+    let mut screen_image = Image::gen_image_color(resolution.0, resolution.1, RED);
+    let mut heatmap_image = Image::gen_image_color(resolution.0, resolution.1, BLACK);
+    let mut screen_texture = Texture2D::from_image(&screen_image);
+
+    let mut adaptive_sampling = AdaptiveSampling::default();
+    let mut use_adaptive_sampling = false;
+    let mut show_heatmap = false;
+    let mut sampling = SamplingMode::default();
+    let mut max_depth = MaxDepth::default();
+    let mut filter = Filter::default();
+    let mut selected_filter = filter;
+    let mut spectral = false;
 
     // let material1 = MaterialKind::Lambertian {
     //     albedo: Color::new(0.8, 0.8, 0.0, 1.0),
@@ -205,40 +160,43 @@ async fn main() {
     //     radius: 0.25,
     //     material: material1,
     // };
-    let sphere2 = Sphere {
-        center: Vec3::new(0.0, 100.5, -1.),
-        radius: 100.,
-        material: MaterialKind::Lambertian {
-            albedo: Color::new(0., 0., 1.0, 1.0),
+
+    let ground = Plane::new(
+        Point::new(0.0, 0.5, 0.),
+        Vec3::new(0., -1., 0.),
+        MaterialKind::TexturedLambertian {
+            albedo: Texture::Checker {
+                even: Color::new(0., 0., 1.0, 1.0).into(),
+                odd: Color::new(0.9, 0.9, 0.9, 1.0).into(),
+                scale: 2.,
+            },
         },
-    };
+    );
 
     let sphere3 = Sphere {
-        center: Vec3::new(-0.5, 0., -1.),
+        center: Point::new(-0.5, 0., -1.),
         radius: 0.25,
         material: MaterialKind::Metal {
-            albedo: Color::new(0.8, 0.8, 0.8, 1.0),
+            albedo: Rgb::new(0.8, 0.8, 0.8),
         },
     };
 
     let sphere4 = Sphere {
-        center: Vec3::new(0.0, 0., -1.),
+        center: Point::new(0.0, 0., -1.),
         radius: -0.25,
         material: MaterialKind::Lambertian {
-            albedo: Color::new(0.7, 0.3, 0.3, 1.0),
+            albedo: Rgb::new(0.7, 0.3, 0.3),
         },
     };
     let sphere5 = Sphere {
-        center: Vec3::new(0.5, 0., -1.),
+        center: Point::new(0.5, 0., -1.),
         radius: 0.25,
-        material: MaterialKind::Metal {
-            albedo: Color::new(0.8, 0.6, 0.2, 1.0),
-        },
+        material: MaterialKind::Conductor(Conductor::gold(0.3)),
     };
 
     let mut world = World::new();
     // world.add(Box::new(sphere1));
-    world.add(Box::new(sphere2));
+    world.add(Box::new(ground));
     world.add(Box::new(sphere3));
     world.add(Box::new(sphere4));
     world.add(Box::new(sphere5));
@@ -286,23 +244,115 @@ async fn main() {
     //     }
     // }
 
-    render_update(&camera, &world, &mut screen_image);
+    render_update(
+        &camera,
+        &world,
+        sampling,
+        max_depth,
+        filter,
+        spectral,
+        &mut screen_image,
+        &mut heatmap_image,
+    );
 
     loop {
         clear_background(LIGHTGRAY);
 
-        screen_texture.update(&screen_image);
-        draw_texture(screen_texture, 0., 0., WHITE);
+        if show_heatmap {
+            screen_texture.update(&heatmap_image);
+        } else {
+            screen_texture.update(&screen_image);
+        }
+        draw_fitted(screen_texture);
 
         //draw fps
         let fps = get_fps().to_string();
         draw_text(&fps, 10., 10., 30., BLACK);
 
+        let mut needs_update = false;
+
         egui_macroquad::ui(|egui_ctx| {
             egui::Window::new("egui ❤ macroquad").show(egui_ctx, |ui| {
                 ui.label("Camera");
                 // camera origin
                 ui.label(format!("origin: {:?}", camera.origin));
+                let shutter =
+                    ui.add(egui::Slider::new(&mut camera.shutter_close, 0.0..=1.0).text("shutter"));
+                if shutter.changed() {
+                    needs_update = true;
+                }
+
+                ui.separator();
+                ui.label("Resolution");
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut selected_resolution.0).clamp_range(1..=7680));
+                    ui.label("x");
+                    ui.add(egui::DragValue::new(&mut selected_resolution.1).clamp_range(1..=4320));
+                });
+                if ui.button("apply resolution").clicked() && selected_resolution != resolution {
+                    resolution = selected_resolution;
+                    camera.set_aspect_ratio(resolution.0 as Float / resolution.1 as Float);
+                    screen_image = Image::gen_image_color(resolution.0, resolution.1, RED);
+                    heatmap_image = Image::gen_image_color(resolution.0, resolution.1, BLACK);
+                    screen_texture.delete();
+                    screen_texture = Texture2D::from_image(&screen_image);
+                    needs_update = true;
+                }
+
+                ui.separator();
+                ui.label("Sampling");
+                ui.checkbox(&mut use_adaptive_sampling, "adaptive sampling");
+                ui.add_enabled_ui(use_adaptive_sampling, |ui| {
+                    ui.add(
+                        egui::Slider::new(&mut adaptive_sampling.threshold, 0.001..=0.1)
+                            .logarithmic(true)
+                            .text("error threshold"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut adaptive_sampling.min_samples, 1..=64)
+                            .text("min spp"),
+                    );
+                    ui.add(
+                        egui::Slider::new(&mut adaptive_sampling.max_samples, 1..=1024)
+                            .logarithmic(true)
+                            .text("max spp"),
+                    );
+                });
+                ui.checkbox(&mut show_heatmap, "show spp heatmap");
+                if ui.checkbox(&mut spectral, "spectral rendering").changed() {
+                    needs_update = true;
+                }
+
+                ui.separator();
+                ui.label("Max bounces");
+                for (depth, label) in [
+                    (&mut max_depth.diffuse, "diffuse"),
+                    (&mut max_depth.specular, "specular"),
+                    (&mut max_depth.transmission, "transmission"),
+                ] {
+                    if ui
+                        .add(egui::Slider::new(depth, 0..=64).text(label))
+                        .changed()
+                    {
+                        needs_update = true;
+                    }
+                }
+
+                ui.separator();
+                ui.label("Reconstruction filter");
+                egui::ComboBox::from_label("filter")
+                    .selected_text(selected_filter.name())
+                    .show_ui(ui, |ui| {
+                        for f in [
+                            Filter::default(),
+                            Filter::tent(),
+                            Filter::gaussian(),
+                            Filter::mitchell(),
+                            Filter::lanczos(),
+                        ] {
+                            ui.selectable_value(&mut selected_filter, f, f.name());
+                        }
+                    });
             });
         });
 
@@ -310,7 +360,22 @@ async fn main() {
 
         egui_macroquad::draw();
 
-        let mut needs_update = false;
+        adaptive_sampling.max_samples = adaptive_sampling
+            .max_samples
+            .max(adaptive_sampling.min_samples);
+        let new_sampling = if use_adaptive_sampling {
+            SamplingMode::Adaptive(adaptive_sampling)
+        } else {
+            SamplingMode::default()
+        };
+        if new_sampling != sampling {
+            sampling = new_sampling;
+            needs_update = true;
+        }
+        if selected_filter != filter {
+            filter = selected_filter;
+            needs_update = true;
+        }
 
         // check AWSD key for camera movement
         if is_key_down(KeyCode::W) {
@@ -348,9 +413,16 @@ async fn main() {
         }
 
         if needs_update {
-            render_update(&camera, &world, &mut screen_image);
-            screen_texture.update(&screen_image);
-            draw_texture(screen_texture, 0., 0., WHITE);
+            render_update(
+                &camera,
+                &world,
+                sampling,
+                max_depth,
+                filter,
+                spectral,
+                &mut screen_image,
+                &mut heatmap_image,
+            );
         }
 
         next_frame().await
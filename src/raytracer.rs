@@ -1,7 +1,38 @@
+pub mod aabb;
+pub mod alpha;
+pub mod bsdf;
+pub mod bump;
 pub mod camera;
+pub mod color;
+pub mod conductor;
+pub mod cone;
+pub mod constant_medium;
+pub mod csg;
+pub mod cuboid;
+pub mod cylinder;
+pub mod dielectric;
+pub mod disk;
+pub mod film;
 pub mod geometry;
 pub mod hit_info;
+pub mod integrator;
+pub mod layered;
 pub mod material;
+pub mod math;
+pub mod microfacet;
+pub mod moving;
+pub mod plane;
+pub mod polynomial;
+pub mod principled;
+pub mod quad;
+pub mod radiance;
 pub mod ray;
+pub mod sampler;
+pub mod sdf;
+pub mod spectrum;
 pub mod sphere;
+pub mod subsurface;
+pub mod texture;
+pub mod torus;
+pub mod volume;
 pub mod world;
@@ -0,0 +1,104 @@
+use super::{
+    math::{Float, Vec3},
+    ray::Ray,
+};
+
+/// Axis-aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    /// Smallest box containing all the given points.
+    pub fn from_points(points: &[Vec3]) -> Self {
+        let mut min = Vec3::splat(Float::INFINITY);
+        let mut max = Vec3::splat(Float::NEG_INFINITY);
+        for p in points {
+            min = min.min(*p);
+            max = max.max(*p);
+        }
+        Self { min, max }
+    }
+
+    pub fn surrounding(a: Aabb, b: Aabb) -> Self {
+        Self {
+            min: a.min.min(b.min),
+            max: a.max.max(b.max),
+        }
+    }
+
+    /// Grows degenerate axes so that flat primitives still have a volume.
+    pub fn padded(self, delta: Float) -> Self {
+        let size = self.max - self.min;
+        let pad = Vec3::new(
+            if size.x < delta { delta / 2. } else { 0. },
+            if size.y < delta { delta / 2. } else { 0. },
+            if size.z < delta { delta / 2. } else { 0. },
+        );
+        Self {
+            min: self.min - pad,
+            max: self.max + pad,
+        }
+    }
+
+    pub fn centroid(&self) -> Vec3 {
+        (self.min + self.max) / 2.
+    }
+
+    /// Slab test.
+    pub fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> bool {
+        self.clip(ray, t_min, t_max).is_some()
+    }
+
+    /// Part of `[t_min, t_max]` for which the ray is inside the box.
+    pub fn clip(&self, ray: &Ray, mut t_min: Float, mut t_max: Float) -> Option<(Float, Float)> {
+        for axis in 0..3 {
+            let inv_d = 1. / ray.direction[axis];
+            let mut t0 = (self.min[axis] - ray.origin[axis]) * inv_d;
+            let mut t1 = (self.max[axis] - ray.origin[axis]) * inv_d;
+            if inv_d < 0. {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_min = t0.max(t_min);
+            t_max = t1.min(t_max);
+            if t_max <= t_min {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::geometry::{Direction, Point};
+    use crate::raytracer::math::{Float, Vec3};
+
+    use crate::raytracer::{aabb::Aabb, ray::Ray};
+
+    #[test]
+    fn test_aabb_hit() {
+        let aabb = Aabb::new(Vec3::new(-1., -1., -1.), Vec3::new(1., 1., 1.));
+
+        let ray = Ray::new(Point::new(0., 0., -5.), Direction::new(0., 0., 1.));
+        assert!(aabb.hit(&ray, 0., Float::INFINITY));
+
+        let ray = Ray::new(Point::new(0., 2., -5.), Direction::new(0., 0., 1.));
+        assert!(!aabb.hit(&ray, 0., Float::INFINITY));
+    }
+
+    #[test]
+    fn test_aabb_padded() {
+        let aabb = Aabb::new(Vec3::new(0., 0., 0.), Vec3::new(1., 1., 0.)).padded(0.01);
+
+        assert_eq!(aabb.min.x, 0.);
+        assert!(aabb.max.z > aabb.min.z);
+    }
+}
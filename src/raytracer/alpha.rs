@@ -0,0 +1,60 @@
+use quad_rand::RandomRange;
+
+use super::{hit_info::HitInfo, math::Float, texture::Texture};
+
+/// How an opacity value decides whether a ray passes through a surface.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlphaMode {
+    /// Surfaces with opacity below the threshold are fully transparent.
+    Threshold(Float),
+    /// A ray passes through with probability `1 - opacity`, which
+    /// renders semi-transparent edges correctly on average.
+    Stochastic,
+}
+
+/// Opacity texture for cut-out geometry such as leaves and fences,
+/// read from the alpha channel of the texture.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlphaMask {
+    pub texture: Texture,
+    pub mode: AlphaMode,
+}
+
+impl AlphaMask {
+    pub fn new(texture: Texture) -> Self {
+        Self {
+            texture,
+            mode: AlphaMode::Threshold(0.5),
+        }
+    }
+
+    pub fn stochastic(texture: Texture) -> Self {
+        Self {
+            texture,
+            mode: AlphaMode::Stochastic,
+        }
+    }
+
+    pub fn opacity(&self, hit_info: &HitInfo) -> Float {
+        self.texture
+            .value(hit_info.u, hit_info.v, hit_info.p)
+            .a
+            .clamp(0., 1.)
+    }
+
+    /// Probability that a ray stops at the hit point rather than passing through.
+    pub fn coverage(&self, hit_info: &HitInfo) -> Float {
+        let opacity = self.opacity(hit_info);
+        match self.mode {
+            AlphaMode::Threshold(threshold) if opacity < threshold => 0.,
+            AlphaMode::Threshold(_) => 1.,
+            AlphaMode::Stochastic => opacity,
+        }
+    }
+
+    /// True if the ray should ignore this hit and continue.
+    pub fn passes_through(&self, hit_info: &HitInfo) -> bool {
+        let coverage = self.coverage(hit_info);
+        coverage < 1. && RandomRange::gen_range(0., 1.) >= coverage
+    }
+}
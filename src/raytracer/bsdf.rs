@@ -0,0 +1,86 @@
+use super::radiance::Rgb;
+use super::{
+    geometry::{orthonormal_basis, Direction},
+    hit_info::{HitInfo, Hittable},
+    math::{Float, Vec3},
+    ray::Ray,
+    subsurface::Subsurface,
+};
+
+/// Direction sampled from a BSDF together with the BSDF value and its pdf.
+#[derive(Debug, Clone, Copy)]
+pub struct BsdfSample {
+    /// sampled incoming light direction, pointing away from the surface
+    pub wi: Vec3,
+    pub f: Rgb,
+    /// solid angle density of `wi`; for delta lobes, the probability of picking that lobe
+    pub pdf: Float,
+    /// true if the direction comes from a perfectly specular lobe
+    pub delta: bool,
+    /// interior to random walk through, set when the sample refracts into a
+    /// subsurface material
+    pub subsurface: Option<Subsurface>,
+}
+
+impl BsdfSample {
+    /// Path throughput factor `f * |cos| / pdf` for the sampled direction,
+    /// with `cos` measured against the shading normal `n`.
+    pub fn weight(&self, n: Vec3) -> Rgb {
+        if self.pdf <= 0. {
+            return Rgb::black();
+        }
+        self.f * (self.wi.dot(n).abs() / self.pdf)
+    }
+
+    /// Ray continuing the path from `hit` on `object` in the sampled direction,
+    /// with the throughput of anything the light passes on the way, or `None`
+    /// if it is absorbed.
+    pub fn continue_path(
+        &self,
+        object: &dyn Hittable,
+        hit: &HitInfo,
+        time: Float,
+    ) -> Option<(Ray, Rgb)> {
+        let ray = hit.spawn_ray(Direction::from_vec3(self.wi), time);
+        match self.subsurface {
+            Some(subsurface) => subsurface.random_walk(object, &ray),
+            None => Some((ray, Rgb::white())),
+        }
+    }
+}
+
+/// Orthonormal frame with `n` as local z axis. BSDFs work in this space,
+/// where `cos(theta)` of a direction is just its z component.
+#[derive(Debug, Clone, Copy)]
+pub struct ShadingFrame {
+    pub t: Vec3,
+    pub b: Vec3,
+    pub n: Vec3,
+}
+
+impl ShadingFrame {
+    pub fn new(n: Vec3) -> Self {
+        let (t, b) = orthonormal_basis(n);
+        Self { t, b, n }
+    }
+
+    /// Frame around the shading normal whose tangent follows the surface `u`
+    /// direction, so anisotropic lobes line up with the parameterization.
+    pub fn from_hit(hit: &HitInfo) -> Self {
+        let n = *hit.shading_normal;
+        let Some(t) = (*hit.dpdu - n * n.dot(*hit.dpdu)).try_normalize() else {
+            return Self::new(n);
+        };
+        let b = n.cross(t);
+        let b = if b.dot(*hit.dpdv) < 0. { -b } else { b };
+        Self { t, b, n }
+    }
+
+    pub fn to_local(self, v: Vec3) -> Vec3 {
+        Vec3::new(v.dot(self.t), v.dot(self.b), v.dot(self.n))
+    }
+
+    pub fn to_world(self, v: Vec3) -> Vec3 {
+        self.t * v.x + self.b * v.y + self.n * v.z
+    }
+}
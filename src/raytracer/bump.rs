@@ -0,0 +1,122 @@
+use super::{
+    bsdf::ShadingFrame,
+    geometry::Normal,
+    hit_info::HitInfo,
+    math::{Float, Vec3},
+    radiance::Rgb,
+    texture::Texture,
+};
+
+/// Texture space step used for finite differences of height maps.
+const HEIGHT_DELTA: Float = 1e-3;
+
+/// Perturbs the shading normal of a hit to add surface detail without geometry.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Bump {
+    /// Tangent space normal map with the x, y and z components encoded in
+    /// the red, green and blue channels as `(n + 1) / 2`.
+    NormalMap { texture: Texture, strength: Float },
+    /// Height field, displacing the surface along its normal by
+    /// `scale * luminance(texture)`.
+    Height { texture: Texture, scale: Float },
+}
+
+impl Bump {
+    pub fn normal_map(texture: Texture) -> Self {
+        Self::NormalMap {
+            texture,
+            strength: 1.,
+        }
+    }
+
+    pub fn height(texture: Texture, scale: Float) -> Self {
+        Self::Height { texture, scale }
+    }
+
+    /// Copy of `hit_info` with the perturbed shading normal.
+    pub fn apply(&self, hit_info: &HitInfo) -> HitInfo {
+        let mut hit = hit_info.clone();
+        hit.shading_normal =
+            Normal::from_vec3(self.shading_normal(hit_info)).face_forward(*hit.normal);
+        hit
+    }
+
+    fn shading_normal(&self, hit: &HitInfo) -> Vec3 {
+        let n = *hit.shading_normal;
+        match self {
+            Self::NormalMap { texture, strength } => {
+                let ShadingFrame { t, b, .. } = ShadingFrame::from_hit(hit);
+                let c = texture.value(hit.u, hit.v, hit.p);
+                let m = Vec3::new(
+                    (2. * c.r - 1.) * strength,
+                    (2. * c.g - 1.) * strength,
+                    2. * c.b - 1.,
+                );
+                (t * m.x + b * m.y + n * m.z).try_normalize().unwrap_or(n)
+            }
+            Self::Height { texture, scale } => {
+                let height = |u, v| scale * Rgb::from(texture.value(u, v, hit.p)).luminance();
+                let d = height(hit.u, hit.v);
+                let d_du = (height(hit.u + HEIGHT_DELTA, hit.v) - d) / HEIGHT_DELTA;
+                let d_dv = (height(hit.u, hit.v + HEIGHT_DELTA) - d) / HEIGHT_DELTA;
+
+                let dpdu = *hit.dpdu + n * d_du;
+                let dpdv = *hit.dpdv + n * d_dv;
+                let ns = dpdu.cross(dpdv).try_normalize().unwrap_or(n);
+                // the parameterization may be left handed
+                let base = hit.dpdu.cross(hit.dpdv);
+                if base.dot(n) < 0. {
+                    -ns
+                } else {
+                    ns
+                }
+            }
+        }
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::Vec3;
+
+    use crate::raytracer::{
+        bump::Bump,
+        color::Color,
+        geometry::{Direction, Normal, Point},
+        hit_info::HitInfo,
+        material::MaterialKind,
+        radiance::Rgb,
+        texture::Texture,
+    };
+
+    fn flat_hit() -> HitInfo {
+        let material = MaterialKind::Lambertian {
+            albedo: Rgb::new(0.5, 0.5, 0.5),
+        };
+        let mut hit = HitInfo::new(true, 1., Point::ZERO, Vec3::ZERO, Normal::Z, material);
+        hit.set_tangents(Direction::X, Direction::Y);
+        hit.set_uv(0.5, 0.5);
+        hit
+    }
+
+    #[test]
+    fn test_flat_maps_keep_normal() {
+        let hit = flat_hit();
+        let flat = Texture::Solid(Color::new(0.5, 0.5, 1., 1.));
+        for bump in [Bump::normal_map(flat.clone()), Bump::height(flat, 0.1)] {
+            let ns = bump.apply(&hit).shading_normal;
+            assert!((*ns - Vec3::Z).length() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_normal_map_tilts_towards_tangent() {
+        let hit = flat_hit();
+        let bump = Bump::normal_map(Texture::Solid(Color::new(0.75, 0.5, 0.9, 1.)));
+        let ns = bump.apply(&hit).shading_normal;
+        assert!(ns.x > 0.3);
+        assert!(ns.z > 0.);
+        assert!((ns.length() - 1.).abs() < 1e-5);
+    }
+}
@@ -1,4 +1,4 @@
-use macroquad::prelude::Vec3;
+use super::math::{Float, Vec3};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Camera {
@@ -6,7 +6,11 @@ pub struct Camera {
     pub lower_left_corner: Vec3,
     pub horizontal: Vec3,
     pub vertical: Vec3,
-    focal_length: f32,
+    focal_length: Float,
+    aspect_ratio: Float,
+    /// time interval during which the shutter is open; rays are spread across it
+    pub shutter_open: Float,
+    pub shutter_close: Float,
 }
 
 impl Default for Camera {
@@ -16,12 +20,10 @@ impl Default for Camera {
 }
 
 impl Camera {
-    pub fn new(origin: Vec3, aspect_ratio: f32, focal_length: f32) -> Self {
+    pub fn new(origin: Vec3, aspect_ratio: Float, focal_length: Float) -> Self {
         let viewport_height = 2.;
         let viewport_width = aspect_ratio * viewport_height;
-        let focal_length = focal_length;
 
-        let origin = origin;
         let horizontal = Vec3::new(viewport_width, 0., 0.);
         let vertical = Vec3::new(0., viewport_height, 0.);
         let lower_left_corner =
@@ -33,18 +35,42 @@ impl Camera {
             horizontal,
             vertical,
             focal_length,
+            aspect_ratio,
+            shutter_open: 0.,
+            shutter_close: 1.,
         }
     }
 
-    pub fn set_focal_length(&mut self, focal_length: f32) {
+    /// Builds a camera whose viewport matches an output image of `width` x `height` pixels.
+    pub fn with_resolution(origin: Vec3, width: usize, height: usize, focal_length: Float) -> Self {
+        Camera::new(origin, width as Float / height as Float, focal_length)
+    }
+
+    pub fn set_focal_length(&mut self, focal_length: Float) {
         self.focal_length = focal_length;
     }
 
-    pub fn focal_length_mut(&mut self) -> &mut f32 {
+    pub fn focal_length_mut(&mut self) -> &mut Float {
         &mut self.focal_length
     }
 
-    pub fn focal_length(&self) -> f32 {
+    pub fn focal_length(&self) -> Float {
         self.focal_length
     }
+
+    pub fn aspect_ratio(&self) -> Float {
+        self.aspect_ratio
+    }
+
+    /// Rebuilds the viewport for a new aspect ratio, keeping origin and focal length.
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: Float) {
+        let (shutter_open, shutter_close) = (self.shutter_open, self.shutter_close);
+        *self = Camera::new(self.origin, aspect_ratio, self.focal_length);
+        self.set_shutter(shutter_open, shutter_close);
+    }
+
+    pub fn set_shutter(&mut self, open: Float, close: Float) {
+        self.shutter_open = open;
+        self.shutter_close = close;
+    }
 }
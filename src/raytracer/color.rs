@@ -0,0 +1,48 @@
+use super::math::Float;
+
+/// RGBA color as stored in textures and images. Light transport uses
+/// [`Rgb`](super::radiance::Rgb) instead; the alpha channel only matters for
+/// cut-out masks.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Color {
+    pub r: Float,
+    pub g: Float,
+    pub b: Float,
+    pub a: Float,
+}
+
+impl Color {
+    pub const fn new(r: Float, g: Float, b: Float, a: Float) -> Self {
+        Self { r, g, b, a }
+    }
+
+    pub const fn rgb(r: Float, g: Float, b: Float) -> Self {
+        Self::new(r, g, b, 1.)
+    }
+
+    pub fn to_array(self) -> [Float; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+}
+
+impl From<[Float; 4]> for Color {
+    fn from([r, g, b, a]: [Float; 4]) -> Self {
+        Self::new(r, g, b, a)
+    }
+}
+
+#[cfg(feature = "app")]
+#[allow(clippy::unnecessary_cast)]
+impl From<macroquad::color::Color> for Color {
+    fn from(c: macroquad::color::Color) -> Self {
+        Self::new(c.r as Float, c.g as Float, c.b as Float, c.a as Float)
+    }
+}
+
+#[cfg(feature = "app")]
+#[allow(clippy::unnecessary_cast)]
+impl From<Color> for macroquad::color::Color {
+    fn from(c: Color) -> Self {
+        Self::new(c.r as f32, c.g as f32, c.b as f32, c.a as f32)
+    }
+}
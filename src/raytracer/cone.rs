@@ -0,0 +1,154 @@
+use super::{
+    aabb::Aabb,
+    geometry::{ray_point_error, Direction, Frame, Normal, Point},
+    hit_info::{HitInfo, Hittable},
+    material::MaterialKind,
+    math::{consts::PI, Float, Vec3},
+    polynomial::{narrow, solve_quadratic, widen},
+    ray::Ray,
+};
+
+/// Cone with a capped base of `radius` at `base` and its apex `height` along `axis`.
+pub struct Cone {
+    pub radius: Float,
+    pub height: Float,
+    pub material: MaterialKind,
+    frame: Frame,
+}
+
+impl Cone {
+    pub fn new(
+        base: Point,
+        axis: Vec3,
+        radius: Float,
+        height: Float,
+        material: MaterialKind,
+    ) -> Self {
+        Self {
+            radius,
+            height,
+            material,
+            frame: Frame::new(base, axis),
+        }
+    }
+}
+
+impl Hittable for Cone {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitInfo> {
+        let o = self.frame.local_point(ray.origin);
+        let d = self.frame.local_direction(ray.direction);
+
+        // (t, local normal, u, v, local dp/du, local dp/dv) of the closest hit so far
+        let mut closest: Option<(Float, Vec3, Float, Float, Vec3, Vec3)> = None;
+        let mut closest_so_far = t_max;
+
+        // side: x^2 + z^2 = k^2 (h - y)^2, 0 <= y <= h
+        let k2 = (self.radius / self.height).powi(2);
+        let h = self.height - o.y;
+        let a = d.x * d.x + d.z * d.z - k2 * d.y * d.y;
+        let b = 2. * (o.x * d.x + o.z * d.z + k2 * h * d.y);
+        let c = o.x * o.x + o.z * o.z - k2 * h * h;
+        for t in solve_quadratic([c, b, a].map(widen))
+            .into_iter()
+            .map(narrow)
+        {
+            if t <= t_min || t >= closest_so_far {
+                continue;
+            }
+            let p = o + d * t;
+            if p.y < 0. || p.y > self.height {
+                continue;
+            }
+            let normal = Vec3::new(p.x, k2 * (self.height - p.y), p.z).normalize();
+            let u = (-p.z).atan2(p.x) / (2. * PI) + 0.5;
+            let dpdu = 2. * PI * Vec3::new(p.z, 0., -p.x);
+            // towards the apex along the surface; undefined at the apex itself
+            let rho = (p.x * p.x + p.z * p.z).sqrt().max(1e-8);
+            let dpdv = Vec3::new(
+                -self.radius * p.x / rho,
+                self.height,
+                -self.radius * p.z / rho,
+            );
+            closest = Some((t, normal, u, p.y / self.height, dpdu, dpdv));
+            closest_so_far = t;
+        }
+
+        // base cap
+        if d.y.abs() > 1e-8 {
+            let t = -o.y / d.y;
+            if t > t_min && t < closest_so_far {
+                let p = o + d * t;
+                if p.x * p.x + p.z * p.z <= self.radius * self.radius {
+                    let u = (p.x / self.radius + 1.) / 2.;
+                    let v = (p.z / self.radius + 1.) / 2.;
+                    let dpdu = Vec3::new(2. * self.radius, 0., 0.);
+                    let dpdv = Vec3::new(0., 0., 2. * self.radius);
+                    closest = Some((t, -Vec3::Y, u, v, dpdu, dpdv));
+                }
+            }
+        }
+
+        let (t, normal, u, v, dpdu, dpdv) = closest?;
+        let normal = self.frame.world_normal(Normal::from_vec3(normal));
+        let p_error = ray_point_error(ray, t);
+        let mut hit_info = HitInfo::new(true, t, ray.at(t), p_error, normal, self.material.clone());
+        hit_info.set_face_normal(ray.direction, normal);
+        hit_info.set_uv(u, v);
+        hit_info.set_tangents(
+            self.frame.world_direction(Direction::from_vec3(dpdu)),
+            self.frame.world_direction(Direction::from_vec3(dpdv)),
+        );
+
+        Some(hit_info)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.frame.world_bounds(
+            Vec3::new(-self.radius, 0., -self.radius),
+            Vec3::new(self.radius, self.height, self.radius),
+        ))
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::{Float, Vec3};
+
+    use crate::raytracer::{
+        cone::Cone,
+        geometry::{Direction, Point},
+        hit_info::Hittable,
+        material::MaterialKind,
+        radiance::Rgb,
+        ray::Ray,
+    };
+
+    #[test]
+    fn test_cone_hit() {
+        let material = MaterialKind::Lambertian {
+            albedo: Rgb::white(),
+        };
+        let cone = Cone::new(Point::ZERO, Vec3::Y, 1., 2., material);
+
+        // side at half the height, where the radius is 0.5
+        let ray = Ray::new(Point::new(0.5, 5., 0.), Direction::new(0., -1., 0.));
+        let hit = cone.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!((hit.t - 4.).abs() < 1e-4);
+        let outward = Vec3::new(1., 0.5, 0.).normalize();
+        assert!((*hit.normal - outward).length() < 1e-4);
+        assert!(hit.front_face);
+        assert!(hit.dpdu.dot(outward).abs() < 1e-4);
+        assert!(hit.dpdv.dot(outward).abs() < 1e-4);
+
+        // up the axis into the base cap
+        let ray = Ray::new(Point::new(0., -5., 0.), Direction::new(0., 1., 0.));
+        let hit = cone.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!((hit.t - 5.).abs() < 1e-4);
+        assert!((*hit.normal + Vec3::Y).length() < 1e-4);
+
+        // parallel to the axis, outside the base
+        let ray = Ray::new(Point::new(2., 5., 0.), Direction::new(0., -1., 0.));
+        assert!(cone.hit(&ray, 0.001, Float::INFINITY).is_none());
+    }
+}
@@ -0,0 +1,135 @@
+use quad_rand::RandomRange;
+
+use super::{
+    aabb::Aabb,
+    geometry::Normal,
+    hit_info::{Crossings, HitInfo, Hittable},
+    material::MaterialKind,
+    math::{Float, Vec3},
+    radiance::Rgb,
+    ray::Ray,
+};
+
+/// Homogeneous fog or smoke filling a closed `boundary`. Rays scatter at an
+/// exponentially distributed distance inside it, so thin media let most light through.
+pub struct ConstantMedium {
+    pub boundary: Box<dyn Hittable>,
+    pub density: Float,
+    pub phase_function: MaterialKind,
+}
+
+impl ConstantMedium {
+    pub fn new(boundary: Box<dyn Hittable>, density: Float, albedo: Rgb) -> Self {
+        Self {
+            boundary,
+            density,
+            phase_function: MaterialKind::Isotropic { albedo },
+        }
+    }
+}
+
+impl ConstantMedium {
+    /// Part of `[t_min, t_max]` that lies inside the boundary.
+    fn inside(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<(Float, Float)> {
+        // find where the ray line enters and leaves the boundary
+        let mut crossings = Crossings::new(
+            self.boundary.as_ref(),
+            ray,
+            Float::NEG_INFINITY,
+            Float::INFINITY,
+        );
+        let enter = crossings.next()?;
+        let exit = crossings.next()?;
+
+        let t_enter = enter.t.max(t_min).max(0.);
+        let t_exit = exit.t.min(t_max);
+        (t_enter < t_exit).then_some((t_enter, t_exit))
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitInfo> {
+        let (t_enter, t_exit) = self.inside(ray, t_min, t_max)?;
+
+        let length = ray.direction.length();
+        let distance_inside = (t_exit - t_enter) * length;
+        let hit_distance = -(1. / self.density) * RandomRange::gen_range(Float::EPSILON, 1.).ln();
+        if hit_distance > distance_inside {
+            return None;
+        }
+
+        let t = t_enter + hit_distance / length;
+        // normal and front face are meaningless inside a volume
+        let mut hit_info = HitInfo::new(
+            true,
+            t,
+            ray.at(t),
+            Vec3::ZERO,
+            Normal::X,
+            self.phase_function.clone(),
+        );
+        hit_info.front_face = true;
+
+        Some(hit_info)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+
+    fn transmittance(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<Float> {
+        let Some((t_enter, t_exit)) = self.inside(ray, t_min, t_max) else {
+            return Some(1.);
+        };
+        let distance_inside = (t_exit - t_enter) * ray.direction.length();
+        Some((-self.density * distance_inside).exp())
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::Float;
+
+    use crate::raytracer::{
+        constant_medium::ConstantMedium,
+        geometry::{Direction, Point},
+        hit_info::Hittable,
+        material::MaterialKind,
+        radiance::Rgb,
+        ray::Ray,
+        sphere::Sphere,
+    };
+
+    fn fog(density: Float) -> ConstantMedium {
+        let boundary = Sphere::new(
+            Point::ZERO,
+            1.,
+            MaterialKind::Lambertian {
+                albedo: Rgb::white(),
+            },
+        );
+        ConstantMedium::new(Box::new(boundary), density, Rgb::white())
+    }
+
+    #[test]
+    fn test_scatter_probability_matches_beer_lambert() {
+        let medium = fog(0.5);
+        // crosses the unit sphere through its center, so d = 2
+        let ray = Ray::new(Point::new(0., 0., -5.), Direction::new(0., 0., 1.));
+
+        let n = 20000;
+        let scattered = (0..n)
+            .filter(|_| medium.hit(&ray, 0.001, Float::INFINITY).is_some())
+            .count();
+        let expected = 1. - (-0.5 * 2. as Float).exp();
+        assert!((scattered as Float / n as Float - expected).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_ray_missing_boundary() {
+        let medium = fog(100.);
+        let ray = Ray::new(Point::new(0., 2., -5.), Direction::new(0., 0., 1.));
+        assert!(medium.hit(&ray, 0.001, Float::INFINITY).is_none());
+    }
+}
@@ -0,0 +1,245 @@
+use super::{
+    aabb::Aabb,
+    cone::Cone,
+    cuboid::Cuboid,
+    cylinder::Cylinder,
+    hit_info::{Crossings, HitInfo, Hittable},
+    math::Float,
+    ray::Ray,
+    sphere::Sphere,
+    torus::Torus,
+};
+
+/// Span of a ray inside a solid, between the surface crossings `enter` and `exit`.
+#[derive(Debug)]
+pub struct Interval {
+    pub enter: HitInfo,
+    pub exit: HitInfo,
+}
+
+/// A closed `Hittable` that can report every entry/exit interval along a ray,
+/// not just the nearest hit.
+pub trait Solid: Hittable {
+    /// All intervals along the whole ray line, sorted by `enter.t`.
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let crossings = Crossings::new(self, ray, Float::NEG_INFINITY, Float::INFINITY);
+
+        let mut intervals = vec![];
+        let mut enter: Option<HitInfo> = None;
+        for info in crossings.take(MAX_CROSSINGS) {
+            if info.front_face {
+                enter = Some(info);
+            } else if let Some(e) = enter.take() {
+                intervals.push(Interval {
+                    enter: e,
+                    exit: info,
+                });
+            }
+        }
+        intervals
+    }
+}
+
+const MAX_CROSSINGS: usize = 32;
+
+impl Solid for Sphere {}
+impl Solid for Cuboid {}
+impl Solid for Cylinder {}
+impl Solid for Cone {}
+impl Solid for Torus {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsgOp {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl CsgOp {
+    fn inside(&self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            Self::Union => in_left || in_right,
+            Self::Intersection => in_left && in_right,
+            Self::Difference => in_left && !in_right,
+        }
+    }
+}
+
+/// Boolean combination of two solids.
+pub struct Csg {
+    pub op: CsgOp,
+    pub left: Box<dyn Solid>,
+    pub right: Box<dyn Solid>,
+}
+
+impl Csg {
+    pub fn new(op: CsgOp, left: Box<dyn Solid>, right: Box<dyn Solid>) -> Self {
+        Self { op, left, right }
+    }
+
+    pub fn union(left: Box<dyn Solid>, right: Box<dyn Solid>) -> Self {
+        Self::new(CsgOp::Union, left, right)
+    }
+
+    pub fn intersection(left: Box<dyn Solid>, right: Box<dyn Solid>) -> Self {
+        Self::new(CsgOp::Intersection, left, right)
+    }
+
+    pub fn difference(left: Box<dyn Solid>, right: Box<dyn Solid>) -> Self {
+        Self::new(CsgOp::Difference, left, right)
+    }
+}
+
+struct Event {
+    info: HitInfo,
+    left: bool,
+    enter: bool,
+}
+
+impl Solid for Csg {
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let mut events = vec![];
+        for (left, intervals) in [
+            (true, self.left.intervals(ray)),
+            (false, self.right.intervals(ray)),
+        ] {
+            for interval in intervals {
+                events.push(Event {
+                    info: interval.enter,
+                    left,
+                    enter: true,
+                });
+                events.push(Event {
+                    info: interval.exit,
+                    left,
+                    enter: false,
+                });
+            }
+        }
+        events.sort_by(|a, b| a.info.t.total_cmp(&b.info.t));
+
+        let mut in_left = false;
+        let mut in_right = false;
+        let mut enter: Option<HitInfo> = None;
+        let mut intervals = vec![];
+
+        for event in events {
+            let was_inside = self.op.inside(in_left, in_right);
+            if event.left {
+                in_left = event.enter;
+            } else {
+                in_right = event.enter;
+            }
+            let is_inside = self.op.inside(in_left, in_right);
+
+            // the stored normal always faces the ray, so a surface that changes role
+            // (e.g. the exit of a subtracted solid becoming an entry) only flips `front_face`
+            let mut info = event.info;
+            if !was_inside && is_inside {
+                info.front_face = true;
+                enter = Some(info);
+            } else if was_inside && !is_inside {
+                info.front_face = false;
+                if let Some(e) = enter.take() {
+                    intervals.push(Interval {
+                        enter: e,
+                        exit: info,
+                    });
+                }
+            }
+        }
+
+        intervals
+    }
+}
+
+impl Hittable for Csg {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitInfo> {
+        self.intervals(ray)
+            .into_iter()
+            .flat_map(|interval| [interval.enter, interval.exit])
+            .find(|info| info.t > t_min && info.t < t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let left = self.left.bounding_box()?;
+        let right = self.right.bounding_box()?;
+
+        Some(match self.op {
+            CsgOp::Union => Aabb::surrounding(left, right),
+            CsgOp::Intersection => Aabb::new(left.min.max(right.min), left.max.min(right.max)),
+            CsgOp::Difference => left,
+        })
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::Float;
+
+    use crate::raytracer::{
+        csg::{Csg, Solid},
+        geometry::{Direction, Point},
+        hit_info::Hittable,
+        material::MaterialKind,
+        radiance::Rgb,
+        ray::Ray,
+        sphere::Sphere,
+    };
+
+    fn sphere(center: Point, radius: Float) -> Box<Sphere> {
+        let material = MaterialKind::Lambertian {
+            albedo: Rgb::white(),
+        };
+        Box::new(Sphere::new(center, radius, material))
+    }
+
+    #[test]
+    fn test_sphere_intervals() {
+        let s = sphere(Point::ZERO, 1.);
+        let ray = Ray::new(Point::new(0., 0., -5.), Direction::Z);
+        let intervals = s.intervals(&ray);
+
+        assert_eq!(intervals.len(), 1);
+        assert!((intervals[0].enter.t - 4.).abs() < 1e-5);
+        assert!((intervals[0].exit.t - 6.).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_csg_difference() {
+        // unit sphere with a smaller sphere bitten out of its front
+        let csg = Csg::difference(
+            sphere(Point::ZERO, 1.),
+            sphere(Point::new(0., 0., -1.), 0.5),
+        );
+        let ray = Ray::new(Point::new(0., 0., -5.), Direction::Z);
+
+        let hit = csg.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!((hit.t - 4.5).abs() < 1e-4);
+        assert!(hit.front_face);
+        assert!(hit.normal.dot(*ray.direction) < 0.);
+
+        let intervals = csg.intervals(&ray);
+        assert_eq!(intervals.len(), 1);
+        assert!((intervals[0].exit.t - 6.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_csg_intersection_and_union() {
+        let ray = Ray::new(Point::new(0., 0., -5.), Direction::Z);
+
+        let lens = Csg::intersection(
+            sphere(Point::new(0., 0., -0.5), 1.),
+            sphere(Point::new(0., 0., 0.5), 1.),
+        );
+        let hit = lens.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!((hit.t - 4.5).abs() < 1e-4);
+
+        let pair = Csg::union(
+            sphere(Point::new(0., 0., -2.), 0.5),
+            sphere(Point::new(0., 0., 2.), 0.5),
+        );
+        assert_eq!(pair.intervals(&ray).len(), 2);
+    }
+}
@@ -0,0 +1,115 @@
+use super::{
+    aabb::Aabb,
+    geometry::Point,
+    hit_info::{HitInfo, Hittable},
+    material::MaterialKind,
+    math::{Float, Vec3},
+    quad::Quad,
+    ray::Ray,
+};
+
+/// Axis-aligned box made of six quads.
+pub struct Cuboid {
+    min: Vec3,
+    max: Vec3,
+    sides: Vec<Quad>,
+}
+
+impl Cuboid {
+    /// Box spanned by two opposite corners `a` and `b`.
+    pub fn new(a: Point, b: Point, material: MaterialKind) -> Self {
+        let min = a.min(*b);
+        let max = a.max(*b);
+
+        let dx = Vec3::new(max.x - min.x, 0., 0.);
+        let dy = Vec3::new(0., max.y - min.y, 0.);
+        let dz = Vec3::new(0., 0., max.z - min.z);
+
+        // edges are ordered so that every quad normal points outwards
+        let sides = vec![
+            Quad::new(Point::new(min.x, min.y, max.z), dx, dy, material.clone()), // front
+            Quad::new(Point::new(max.x, min.y, max.z), -dz, dy, material.clone()), // right
+            Quad::new(Point::new(max.x, min.y, min.z), -dx, dy, material.clone()), // back
+            Quad::new(Point::new(min.x, min.y, min.z), dz, dy, material.clone()), // left
+            Quad::new(Point::new(min.x, max.y, max.z), dx, -dz, material.clone()), // top
+            Quad::new(Point::new(min.x, min.y, min.z), dx, dz, material),         // bottom
+        ];
+
+        Self { min, max, sides }
+    }
+
+    pub fn sides(&self) -> &[Quad] {
+        &self.sides
+    }
+}
+
+impl Hittable for Cuboid {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitInfo> {
+        let mut closest_so_far = t_max;
+        let mut result = None;
+
+        for side in &self.sides {
+            if let Some(info) = side.hit(ray, t_min, closest_so_far) {
+                closest_so_far = info.t;
+                result = Some(info);
+            }
+        }
+
+        result
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::new(self.min, self.max).padded(1e-4))
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::{Float, Vec3};
+
+    use crate::raytracer::{
+        cuboid::Cuboid,
+        geometry::{Direction, Point},
+        hit_info::Hittable,
+        material::MaterialKind,
+        radiance::Rgb,
+        ray::Ray,
+    };
+
+    #[test]
+    fn test_cuboid_normals_point_outwards() {
+        let material = MaterialKind::Lambertian {
+            albedo: Rgb::white(),
+        };
+        let cuboid = Cuboid::new(Point::new(-1., -1., -1.), Point::new(1., 1., 1.), material);
+
+        for side in cuboid.sides() {
+            let center = *side.q + (side.u + side.v) / 2.;
+            assert!(side.normal().dot(center) > 0.);
+        }
+    }
+
+    #[test]
+    fn test_cuboid_hit_from_inside_and_outside() {
+        let material = MaterialKind::Lambertian {
+            albedo: Rgb::white(),
+        };
+        let cuboid = Cuboid::new(Point::new(-1., -1., -1.), Point::new(1., 1., 1.), material);
+
+        let ray = Ray::new(Point::new(0., 0., 0.), Direction::new(1., 0., 0.));
+        let hit = cuboid.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!((hit.t - 1.).abs() < 1e-6);
+        assert!(!hit.front_face);
+        assert!(hit.normal.dot(Vec3::X) < 0.);
+
+        let ray = Ray::new(Point::new(0., 5., 0.), Direction::new(0., -1., 0.));
+        let hit = cuboid.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!((hit.t - 4.).abs() < 1e-6);
+        assert!(hit.front_face);
+        assert!(hit.normal.dot(Vec3::Y) > 0.);
+
+        let ray = Ray::new(Point::new(0., 5., 0.), Direction::new(1., 0., 0.));
+        assert!(cuboid.hit(&ray, 0.001, Float::INFINITY).is_none());
+    }
+}
@@ -0,0 +1,209 @@
+use quad_rand::RandomRange;
+
+use super::{
+    bsdf::{BsdfSample, ShadingFrame},
+    math::{Float, Vec2, Vec3},
+    microfacet::{DielectricLobe, TrowbridgeReitz},
+    radiance::Rgb,
+    spectrum::SampledWavelengths,
+};
+
+/// Wavelength dependence of the index of refraction, only used in spectral mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dispersion {
+    None,
+    /// `n = a + b / lambda^2`, with `lambda` in micrometers.
+    Cauchy {
+        a: Float,
+        b: Float,
+    },
+    /// `n^2 = 1 + sum(b_i lambda^2 / (lambda^2 - c_i))`, with `lambda` in micrometers.
+    Sellmeier {
+        b: [Float; 3],
+        c: [Float; 3],
+    },
+}
+
+impl Dispersion {
+    /// Index of refraction at `lambda` nm, or `None` without dispersion.
+    pub fn ior(&self, lambda: Float) -> Option<Float> {
+        let l2 = (lambda / 1000.) * (lambda / 1000.);
+        match *self {
+            Self::None => None,
+            Self::Cauchy { a, b } => Some(a + b / l2),
+            Self::Sellmeier { b, c } => {
+                let n2 = 1. + (0..3).map(|i| b[i] * l2 / (l2 - c[i])).sum::<Float>();
+                Some(n2.max(1.).sqrt())
+            }
+        }
+    }
+}
+
+/// Glass-like GGX microfacet interface that reflects and refracts;
+/// zero roughness gives a perfectly smooth surface.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dielectric {
+    pub ior: Float,
+    pub roughness: Float,
+    pub anisotropy: Float,
+    pub tint: Rgb,
+    pub dispersion: Dispersion,
+}
+
+impl Dielectric {
+    pub fn new(ior: Float, roughness: Float) -> Self {
+        Self {
+            ior,
+            roughness,
+            anisotropy: 0.,
+            tint: Rgb::white(),
+            dispersion: Dispersion::None,
+        }
+    }
+
+    pub fn glass(roughness: Float) -> Self {
+        Self::new(1.5, roughness)
+    }
+
+    pub fn water(roughness: Float) -> Self {
+        Self::new(1.33, roughness)
+    }
+
+    pub fn diamond(roughness: Float) -> Self {
+        Self::new(2.42, roughness)
+    }
+
+    /// Schott N-BK7 crown glass.
+    pub fn bk7(roughness: Float) -> Self {
+        Self::new(1.5168, roughness).with_dispersion(Dispersion::Sellmeier {
+            b: [1.039_612, 0.231_792_34, 1.010_469_5],
+            c: [0.006_000_699, 0.020_017_914, 103.560_65],
+        })
+    }
+
+    /// Schott SF11 dense flint glass, with strong dispersion.
+    pub fn sf11(roughness: Float) -> Self {
+        Self::new(1.7847, roughness).with_dispersion(Dispersion::Sellmeier {
+            b: [1.737_597, 0.313_747_35, 1.898_781],
+            c: [0.013_188_707, 0.062_306_814, 155.236_3],
+        })
+    }
+
+    pub fn with_dispersion(mut self, dispersion: Dispersion) -> Self {
+        self.dispersion = dispersion;
+        self
+    }
+
+    /// True if sampling this interface in spectral mode must terminate all
+    /// but the hero wavelength.
+    pub fn is_dispersive(&self) -> bool {
+        self.dispersion != Dispersion::None
+    }
+
+    /// Copy with the index of refraction of the hero wavelength and the tint
+    /// converted to the sampled wavelengths.
+    pub fn at_wavelengths(&self, wavelengths: &SampledWavelengths) -> Self {
+        Self {
+            ior: self
+                .dispersion
+                .ior(wavelengths.lambda[0])
+                .unwrap_or(self.ior),
+            tint: wavelengths.upsample(self.tint),
+            dispersion: Dispersion::None,
+            ..*self
+        }
+    }
+
+    pub fn with_anisotropy(mut self, anisotropy: Float) -> Self {
+        self.anisotropy = anisotropy;
+        self
+    }
+
+    pub fn with_tint(mut self, tint: Rgb) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    pub fn is_smooth(&self) -> bool {
+        TrowbridgeReitz::from_roughness_anisotropic(self.roughness, self.anisotropy).is_smooth()
+    }
+
+    fn lobe(&self, front_face: bool) -> DielectricLobe {
+        DielectricLobe {
+            distribution: TrowbridgeReitz::from_roughness_anisotropic(
+                self.roughness,
+                self.anisotropy,
+            ),
+            eta: if front_face { self.ior } else { 1. / self.ior },
+            tint: self.tint,
+        }
+    }
+
+    /// World-space evaluation in `frame`, whose normal is the shading normal facing `wo`.
+    pub fn eval(&self, wo: Vec3, wi: Vec3, frame: ShadingFrame, front_face: bool) -> Rgb {
+        self.lobe(front_face)
+            .eval(frame.to_local(wo), frame.to_local(wi))
+    }
+
+    pub fn pdf(&self, wo: Vec3, wi: Vec3, frame: ShadingFrame, front_face: bool) -> Float {
+        self.lobe(front_face)
+            .pdf(frame.to_local(wo), frame.to_local(wi))
+    }
+
+    pub fn sample(&self, wo: Vec3, frame: ShadingFrame, front_face: bool) -> Option<BsdfSample> {
+        let u_lobe = RandomRange::gen_range(0., 1.);
+        let u = Vec2::new(
+            RandomRange::gen_range(0., 1.),
+            RandomRange::gen_range(0., 1.),
+        );
+        let sample = self
+            .lobe(front_face)
+            .sample(frame.to_local(wo), u_lobe, u)?;
+        Some(BsdfSample {
+            wi: frame.to_world(sample.wi),
+            ..sample
+        })
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::Vec3;
+
+    use crate::raytracer::{bsdf::ShadingFrame, dielectric::Dielectric};
+
+    #[test]
+    fn test_sellmeier_dispersion() {
+        let glass = Dielectric::bk7(0.);
+        let n_d = glass.dispersion.ior(587.6).unwrap();
+        assert!((n_d - glass.ior).abs() < 1e-3);
+        // blue light bends more than red light
+        assert!(glass.dispersion.ior(486.1).unwrap() > glass.dispersion.ior(656.3).unwrap());
+    }
+
+    /// The sampled value and pdf must match eval and pdf for both the reflected
+    /// and the transmitted lobe of rough anisotropic glass.
+    #[test]
+    fn test_anisotropic_sample_consistent_with_eval_and_pdf() {
+        let glass = Dielectric::new(1.5, 0.3).with_anisotropy(0.8);
+        let frame = ShadingFrame {
+            t: Vec3::Z,
+            b: Vec3::X,
+            n: Vec3::Y,
+        };
+        let wo = Vec3::new(0.3, 0.9, -0.2).normalize();
+
+        for front_face in [true, false] {
+            for _ in 0..200 {
+                if let Some(s) = glass.sample(wo, frame, front_face) {
+                    assert!(!s.delta);
+                    let f = glass.eval(wo, s.wi, frame, front_face);
+                    assert!((f.r - s.f.r).abs() <= 1e-3 * f.r.max(1.));
+                    let pdf = glass.pdf(wo, s.wi, frame, front_face);
+                    assert!((pdf - s.pdf).abs() <= 1e-3 * pdf.max(1.));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use super::{
+    aabb::Aabb,
+    geometry::{orthonormal_basis, reproject_onto_plane, Direction, Normal, Point},
+    hit_info::{HitInfo, Hittable},
+    material::MaterialKind,
+    math::{consts::PI, Float, Vec3},
+    ray::Ray,
+};
+
+/// Flat disk facing along `normal`.
+pub struct Disk {
+    pub center: Point,
+    pub normal: Vec3,
+    pub radius: Float,
+    pub material: MaterialKind,
+}
+
+impl Disk {
+    pub fn new(center: Point, normal: Vec3, radius: Float, material: MaterialKind) -> Self {
+        Self {
+            center,
+            normal: normal.normalize(),
+            radius,
+            material,
+        }
+    }
+}
+
+impl Hittable for Disk {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitInfo> {
+        let denom = self.normal.dot(*ray.direction);
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = self.normal.dot(*(self.center - ray.origin)) / denom;
+        if t <= t_min || t >= t_max {
+            return None;
+        }
+
+        let (p, p_error) = reproject_onto_plane(ray.at(t), self.center, self.normal);
+        let offset = *(p - self.center);
+        let distance = offset.length();
+        if distance > self.radius {
+            return None;
+        }
+
+        // polar coordinates: u goes around the disk, v from the center to the rim
+        let (tangent, bitangent) = orthonormal_basis(self.normal);
+        let (x, y) = (offset.dot(tangent), offset.dot(bitangent));
+        let phi = y.atan2(x) + PI;
+        let dpdu = 2. * PI * (bitangent * x - tangent * y);
+        let dpdv = offset.try_normalize().unwrap_or(tangent) * self.radius;
+
+        let mut hit_info = HitInfo::new(
+            true,
+            t,
+            p,
+            p_error,
+            Normal::from_vec3(self.normal),
+            self.material.clone(),
+        );
+        hit_info.set_face_normal(ray.direction, Normal::from_vec3(self.normal));
+        hit_info.set_uv(phi / (2. * PI), distance / self.radius);
+        hit_info.set_tangents(Direction::from_vec3(dpdu), Direction::from_vec3(dpdv));
+
+        Some(hit_info)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // extent of a disk along each axis is radius * sin(angle between axis and normal)
+        let n = self.normal;
+        let e = Vec3::new(
+            (1. - n.x * n.x).max(0.).sqrt(),
+            (1. - n.y * n.y).max(0.).sqrt(),
+            (1. - n.z * n.z).max(0.).sqrt(),
+        ) * self.radius;
+        Some(Aabb::new(*self.center - e, *self.center + e).padded(1e-4))
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::{Float, Vec3};
+
+    use crate::raytracer::{
+        disk::Disk,
+        geometry::{Direction, Point},
+        hit_info::Hittable,
+        material::MaterialKind,
+        radiance::Rgb,
+        ray::Ray,
+    };
+
+    #[test]
+    fn test_disk_hit_uv() {
+        let material = MaterialKind::Lambertian {
+            albedo: Rgb::white(),
+        };
+        let disk = Disk::new(Point::new(0., 0., -2.), Vec3::new(0., 0., 1.), 1., material);
+
+        let ray = Ray::new(Point::new(0.5, 0., 0.), Direction::new(0., 0., -1.));
+        let hit = disk.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!((hit.t - 2.).abs() < 1e-6);
+        assert!((hit.v - 0.5).abs() < 1e-6);
+        assert!((0. ..=1.).contains(&hit.u));
+        assert!(hit.front_face);
+        assert!(hit.dpdu.cross(hit.dpdv).normalize().dot(Vec3::Z).abs() > 0.999);
+
+        let ray = Ray::new(Point::new(0., 0., -4.), Direction::new(0., 0., 1.));
+        let hit = disk.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!(!hit.front_face);
+
+        let ray = Ray::new(Point::new(1.5, 0., 0.), Direction::new(0., 0., -1.));
+        assert!(disk.hit(&ray, 0.001, Float::INFINITY).is_none());
+    }
+}
@@ -0,0 +1,258 @@
+use super::{
+    math::{consts::PI, Float},
+    radiance::Rgb,
+};
+
+/// Reconstruction filter used to weight a sample's contribution
+/// to the pixels around it. All filters are separable.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filter {
+    Box { radius: Float },
+    Tent { radius: Float },
+    Gaussian { radius: Float, alpha: Float },
+    Mitchell { radius: Float, b: Float, c: Float },
+    Lanczos { radius: Float, tau: Float },
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Filter::Box { radius: 0.5 }
+    }
+}
+
+impl Filter {
+    pub fn gaussian() -> Self {
+        Filter::Gaussian {
+            radius: 1.5,
+            alpha: 2.,
+        }
+    }
+
+    pub fn tent() -> Self {
+        Filter::Tent { radius: 1. }
+    }
+
+    pub fn mitchell() -> Self {
+        Filter::Mitchell {
+            radius: 2.,
+            b: 1. / 3.,
+            c: 1. / 3.,
+        }
+    }
+
+    pub fn lanczos() -> Self {
+        Filter::Lanczos {
+            radius: 2.,
+            tau: 2.,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Box { .. } => "box",
+            Self::Tent { .. } => "tent",
+            Self::Gaussian { .. } => "gaussian",
+            Self::Mitchell { .. } => "mitchell-netravali",
+            Self::Lanczos { .. } => "lanczos",
+        }
+    }
+
+    pub fn radius(&self) -> Float {
+        match self {
+            Self::Box { radius }
+            | Self::Tent { radius }
+            | Self::Gaussian { radius, .. }
+            | Self::Mitchell { radius, .. }
+            | Self::Lanczos { radius, .. } => *radius,
+        }
+    }
+
+    /// Filter weight for a sample at offset `(dx, dy)` from a pixel center.
+    pub fn evaluate(&self, dx: Float, dy: Float) -> Float {
+        self.evaluate_1d(dx) * self.evaluate_1d(dy)
+    }
+
+    fn evaluate_1d(&self, x: Float) -> Float {
+        let x = x.abs();
+        if x > self.radius() {
+            return 0.;
+        }
+
+        match *self {
+            Self::Box { .. } => 1.,
+            Self::Tent { radius } => radius - x,
+            Self::Gaussian { radius, alpha } => {
+                ((-alpha * x * x).exp() - (-alpha * radius * radius).exp()).max(0.)
+            }
+            Self::Mitchell { radius, b, c } => mitchell_1d(2. * x / radius, b, c),
+            // windowed sinc; the radius cutoff is applied above
+            Self::Lanczos { tau, .. } => sinc(x) * sinc(x / tau),
+        }
+    }
+}
+
+fn mitchell_1d(x: Float, b: Float, c: Float) -> Float {
+    let x2 = x * x;
+    let x3 = x2 * x;
+    if x < 1. {
+        ((12. - 9. * b - 6. * c) * x3 + (-18. + 12. * b + 6. * c) * x2 + (6. - 2. * b)) / 6.
+    } else if x < 2. {
+        ((-b - 6. * c) * x3
+            + (6. * b + 30. * c) * x2
+            + (-12. * b - 48. * c) * x
+            + (8. * b + 24. * c))
+            / 6.
+    } else {
+        0.
+    }
+}
+
+fn sinc(x: Float) -> Float {
+    if x.abs() < 1e-5 {
+        return 1.;
+    }
+    let px = PI * x;
+    px.sin() / px
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct FilmPixel {
+    sum: Rgb,
+    weight_sum: Float,
+}
+
+/// Accumulates radiance samples and reconstructs the final image,
+/// splatting every sample onto all pixels within the filter radius.
+pub struct Film {
+    width: usize,
+    height: usize,
+    filter: Filter,
+    pixels: Vec<FilmPixel>,
+}
+
+impl Film {
+    pub fn new(width: usize, height: usize, filter: Filter) -> Self {
+        Self {
+            width,
+            height,
+            filter,
+            pixels: vec![FilmPixel::default(); width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn filter(&self) -> Filter {
+        self.filter
+    }
+
+    /// Adds a sample taken at continuous film position `(px, py)`,
+    /// where pixel `(x, y)` covers `[x, x + 1) x [y, y + 1)`.
+    pub fn add_sample(&mut self, px: Float, py: Float, color: Rgb) {
+        let radius = self.filter.radius();
+        // pixel centers are at half-integer positions
+        let x0 = (px - 0.5 - radius).ceil().max(0.) as usize;
+        let x1 = ((px - 0.5 + radius).floor() as isize).min(self.width as isize - 1);
+        let y0 = (py - 0.5 - radius).ceil().max(0.) as usize;
+        let y1 = ((py - 0.5 + radius).floor() as isize).min(self.height as isize - 1);
+
+        if x1 < 0 || y1 < 0 {
+            return;
+        }
+
+        for y in y0..=y1 as usize {
+            for x in x0..=x1 as usize {
+                let weight = self
+                    .filter
+                    .evaluate(x as Float + 0.5 - px, y as Float + 0.5 - py);
+                if weight == 0. {
+                    continue;
+                }
+
+                let pixel = &mut self.pixels[y * self.width + x];
+                pixel.sum += color * weight;
+                pixel.weight_sum += weight;
+            }
+        }
+    }
+
+    /// Filtered color of pixel `(x, y)`.
+    pub fn pixel(&self, x: usize, y: usize) -> Rgb {
+        let pixel = &self.pixels[y * self.width + x];
+        if pixel.weight_sum <= 0. {
+            return Rgb::black();
+        }
+
+        // negative lobes (mitchell, lanczos) can push values below zero
+        (pixel.sum / pixel.weight_sum).map(|c| c.max(0.))
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::consts::{FRAC_PI_4, PI};
+
+    use crate::raytracer::{
+        film::{Film, Filter},
+        radiance::Rgb,
+    };
+
+    #[test]
+    fn test_box_filter_averages_pixel_samples() {
+        let mut film = Film::new(2, 2, Filter::default());
+        film.add_sample(0.25, 0.25, Rgb::new(1., 0., 0.));
+        film.add_sample(0.75, 0.75, Rgb::black());
+
+        let c = film.pixel(0, 0);
+        assert!((c.r - 0.5).abs() < 1e-6);
+
+        let empty = film.pixel(1, 1);
+        assert_eq!(empty.r, 0.);
+    }
+
+    #[test]
+    fn test_wide_filter_splats_to_neighbours() {
+        let mut film = Film::new(3, 1, Filter::tent());
+        film.add_sample(1.5, 0.5, Rgb::white());
+        film.add_sample(1.2, 0.5, Rgb::white());
+
+        assert!(film.pixel(0, 0).r > 0.);
+        assert!(film.pixel(1, 0).r > 0.);
+        assert_eq!(film.pixel(2, 0).r, 0.);
+    }
+
+    #[test]
+    fn test_filters_peak_at_center() {
+        for filter in [
+            Filter::default(),
+            Filter::tent(),
+            Filter::gaussian(),
+            Filter::mitchell(),
+            Filter::lanczos(),
+        ] {
+            let center = filter.evaluate(0., 0.);
+            assert!(center > 0.);
+            assert!(filter.evaluate(0.4, 0.) <= center);
+            assert_eq!(filter.evaluate(filter.radius() + 0.1, 0.), 0.);
+        }
+    }
+
+    #[test]
+    fn test_lanczos_is_windowed_sinc() {
+        let filter = Filter::lanczos();
+
+        // sinc(0.5) * sinc(0.25)
+        let expected = (2. / PI) * (FRAC_PI_4.sin() / FRAC_PI_4);
+        assert!((filter.evaluate(0.5, 0.) - expected).abs() < 1e-5);
+        assert!(filter.evaluate(1., 0.).abs() < 1e-5);
+        // negative lobe between the first and second zero
+        assert!(filter.evaluate(1.5, 0.) < 0.);
+    }
+}
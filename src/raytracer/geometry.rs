@@ -1,6 +1,14 @@
-use macroquad::{
-    prelude::{Mat4, Vec3, Vec4},
-    rand::RandomRange,
+use std::{
+    marker::PhantomData,
+    ops::{Add, AddAssign, Deref, Div, Mul, Neg, Sub},
+};
+
+use quad_rand::RandomRange;
+
+use super::{
+    aabb::Aabb,
+    math::{consts, Float, Mat4, Vec3},
+    ray::Ray,
 };
 
 pub fn random_in_hemisphere(normal: Vec3) -> Vec3 {
@@ -18,229 +26,467 @@ pub fn reflect(v: Vec3, n: Vec3) -> Vec3 {
 }
 
 pub fn random_unit_vector() -> Vec3 {
-    let a = RandomRange::gen_range(0., 2. * std::f32::consts::PI);
-    let z = RandomRange::gen_range(-1., 1.) as f32;
+    let a = RandomRange::gen_range(0., 2. * consts::PI);
+    let z = RandomRange::gen_range(-1., 1.) as Float;
     let r = (1.0 - z * z).sqrt();
     Vec3::new(r * a.cos(), r * a.sin(), z)
 }
 
+/// Two unit vectors that, together with `n`, form an orthonormal basis.
+pub fn orthonormal_basis(n: Vec3) -> (Vec3, Vec3) {
+    let a = if n.x.abs() > 0.9 {
+        Vec3::new(0., 1., 0.)
+    } else {
+        Vec3::new(1., 0., 0.)
+    };
+    let t = n.cross(a).normalize();
+    let b = n.cross(t);
+    (t, b)
+}
+
+/// Bound on the relative error accumulated by `n` floating-point operations
+/// (the γn of pbrt).
+pub fn gamma(n: u32) -> Float {
+    let e = n as Float * Float::EPSILON * 0.5;
+    e / (1. - e)
+}
+
+/// Absolute error bound of `ray.at(t)`, for hit points that are not
+/// reprojected onto their surface. Only holds if `t` itself is accurate to a
+/// few ulps, as it is coming out of the f64 polynomial solvers.
+pub fn ray_point_error(ray: &Ray, t: Float) -> Vec3 {
+    gamma(7) * (ray.origin.abs() + (*ray.direction * t).abs())
+}
+
+/// `p` moved onto the plane through `origin` with unit normal `n`, along with
+/// the error bound of the projected point.
+pub fn reproject_onto_plane(p: Point, origin: Point, n: Vec3) -> (Point, Vec3) {
+    let p = *p - n * n.dot(*(p - origin));
+    (Point::from_vec3(p), gamma(7) * (p.abs() + origin.abs()))
+}
+
+/// Origin for a ray leaving `p` towards `w`, pushed along the geometric
+/// normal `n` past the error bound `p_error`, so that the ray cannot hit the
+/// surface it starts on again. The result is rounded away from `p`.
+pub fn offset_ray_origin(p: Point, p_error: Vec3, n: Normal, w: Direction) -> Point {
+    let d = n.abs().dot(p_error);
+    let mut offset = *n * d;
+    if w.dot(*n) < 0. {
+        offset = -offset;
+    }
+
+    let mut po = *p + offset;
+    for i in 0..3 {
+        if offset[i] > 0. {
+            po[i] = po[i].next_up();
+        } else if offset[i] < 0. {
+            po[i] = po[i].next_down();
+        }
+    }
+    Point::from_vec3(po)
+}
+
+/// Local coordinate frame used by primitives defined around the y axis.
+#[derive(Debug, Clone, Copy)]
+pub struct Frame {
+    pub origin: Point,
+    pub x: Vec3,
+    pub y: Vec3,
+    pub z: Vec3,
+}
+
+impl Frame {
+    /// Frame centered at `origin` whose y axis points along `axis`.
+    pub fn new(origin: Point, axis: Vec3) -> Self {
+        let y = axis.normalize();
+        let (x, z) = orthonormal_basis(y);
+        Self { origin, x, y, z }
+    }
+
+    pub fn local_point(&self, p: Point) -> Point {
+        Point::from_vec3(self.local_vec3((p - self.origin).data))
+    }
+
+    pub fn local_direction(&self, d: Direction) -> Direction {
+        Direction::from_vec3(self.local_vec3(d.data))
+    }
+
+    pub fn world_point(&self, p: Point) -> Point {
+        self.origin + Direction::from_vec3(self.world_vec3(p.data))
+    }
+
+    pub fn world_direction(&self, d: Direction) -> Direction {
+        Direction::from_vec3(self.world_vec3(d.data))
+    }
+
+    /// The frame is orthonormal, so normals rotate like directions.
+    pub fn world_normal(&self, n: Normal) -> Normal {
+        Normal::from_vec3(self.world_vec3(n.data))
+    }
+
+    fn local_vec3(&self, v: Vec3) -> Vec3 {
+        Vec3::new(v.dot(self.x), v.dot(self.y), v.dot(self.z))
+    }
+
+    fn world_vec3(&self, v: Vec3) -> Vec3 {
+        self.x * v.x + self.y * v.y + self.z * v.z
+    }
+
+    /// World-space bounds of a box given in local coordinates.
+    pub fn world_bounds(&self, min: Vec3, max: Vec3) -> Aabb {
+        let mut corners = [Vec3::ZERO; 8];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            let local = Vec3::new(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            );
+            *corner = *self.world_point(Point::from_vec3(local));
+        }
+        Aabb::from_points(&corners)
+    }
+}
+
 pub fn near_zero(v: Vec3) -> bool {
     let s = 1e-8;
 
     v.x.abs() < s && v.y.abs() < s && v.z.abs() < s
 }
 
-// #[derive(Debug, Clone, Copy, PartialEq)]
-// pub struct PointVector {}
-
-// #[derive(Debug, Clone, Copy, PartialEq)]
-// pub struct DirectionVector {}
-
-// #[derive(Debug, Clone, Copy)]
-// pub struct Vector<T> {
-//     pub data: Vec3,
-//     kind: PhantomData<T>,
-// }
-
-// impl<T> Vector<T> {
-//     pub fn new(x: f32, y: f32, z: f32) -> Self {
-//         Self {
-//             data: Vec3::new(x, y, z),
-//             kind: PhantomData,
-//         }
-//     }
-// }
-
-// impl<T: PartialEq> Eq for Vector<T> {}
-
-// impl<T: PartialEq> PartialEq for Vector<T> {
-//     fn eq(&self, other: &Self) -> bool {
-//         self.data == other.data
-//     }
-// }
-
-// pub type Point = Vector<PointVector>;
-// pub type Direction = Vector<DirectionVector>;
-
-// impl Add<Direction> for Point {
-//     type Output = Point;
-
-//     fn add(self, rhs: Direction) -> Self::Output {
-//         Point::new(
-//             self.data.x + rhs.data.x,
-//             self.data.y + rhs.data.y,
-//             self.data.z + rhs.data.z,
-//         )
-//     }
-// }
-
-// impl Add<Direction> for Direction {
-//     type Output = Direction;
-
-//     fn add(self, rhs: Direction) -> Self::Output {
-//         Direction::new(
-//             self.data.x + rhs.data.x,
-//             self.data.y + rhs.data.y,
-//             self.data.z + rhs.data.z,
-//         )
-//     }
-// }
-
-// impl Add<Point> for Point {
-//     type Output = Point;
-
-//     fn add(self, rhs: Point) -> Self::Output {
-//         Point::new(
-//             self.data.x + rhs.data.x,
-//             self.data.y + rhs.data.y,
-//             self.data.z + rhs.data.z,
-//         )
-//     }
-// }
-
-// impl Sub<Point> for Point {
-//     type Output = Point;
-
-//     fn sub(self, rhs: Point) -> Self::Output {
-//         Point::new(
-//             self.data.x - rhs.data.x,
-//             self.data.y - rhs.data.y,
-//             self.data.z - rhs.data.z,
-//         )
-//     }
-// }
-
-// impl Sub<Direction> for Point {
-//     type Output = Point;
-
-//     fn sub(self, rhs: Direction) -> Self::Output {
-//         Point::new(
-//             self.data.x - rhs.data.x,
-//             self.data.y - rhs.data.y,
-//             self.data.z - rhs.data.z,
-//         )
-//     }
-// }
-
-// impl Sub<Direction> for Direction {
-//     type Output = Direction;
-
-//     fn sub(self, rhs: Direction) -> Self::Output {
-//         Direction::new(
-//             self.data.x - rhs.data.x,
-//             self.data.y - rhs.data.y,
-//             self.data.z - rhs.data.z,
-//         )
-//     }
-// }
-
-// impl<T> Mul<f32> for Vector<T> {
-//     type Output = Vector<T>;
-
-//     fn mul(self, rhs: f32) -> Self::Output {
-//         Vector::new(self.data.x * rhs, self.data.y * rhs, self.data.z * rhs)
-//     }
-// }
-
-// impl Mul<Point> for Mat4 {
-//     type Output = Point;
-
-//     fn mul(self, other: Point) -> Self::Output {
-//         let v = Vec4::new(other.data.x, other.data.y, other.data.z, 1.0);
-//         let result = self * v;
-
-//         Point::new(result.x, result.y, result.z)
-//     }
-// }
-
-// //get vector kind
-
-// fn is_point(_vector: Point) -> bool {
-//     _vector.kind == PhantomData::<PointVector>
-// }
-
-// fn is_direction(_vector: Direction) -> bool {
-//     _vector.kind == PhantomData::<DirectionVector>
-// }
-
-// // unit tests
-// #[cfg(test)]
-// mod tests {
-//     use macroquad::prelude::Mat4;
-
-//     use super::*;
-
-//     #[test]
-//     fn test_point() {
-//         let p = Point::new(1.0, 2.0, 3.0);
-//         assert_eq!(p.data.x, 1.0);
-//         assert_eq!(p.data.y, 2.0);
-//         assert_eq!(p.data.z, 3.0);
-//     }
-
-//     #[test]
-//     fn test_point_add_direction() {
-//         let p = Point::new(1.0, 2.0, 3.0);
-//         let d = Direction::new(1.0, 2.0, 3.0);
-//         let p2 = p + d;
-
-//         assert_eq!(p2.data.x, 2.0);
-//         assert!(is_point(p2));
-//         // assert!(is_direction(p2));
-//     }
-
-//     #[test]
-//     fn test_direction_point_sub() {
-//         let p = Point::new(3., 2., 1.);
-//         let d = Direction::new(5., 6., 7.);
-//         let p2 = p - d;
-
-//         assert_eq!(p2.data.x, -2.);
-//         assert_eq!(p2.data.y, -4.);
-//         assert_eq!(p2.data.z, -6.);
-
-//         assert!(is_point(p2));
-//         // assert!(is_direction(p2));
-//     }
-
-//     #[test]
-//     fn test_direction_direction_sub() {
-//         let d1 = Direction::new(3., 2., 1.);
-//         let d2 = Direction::new(5., 6., 7.);
-//         let d3 = d1 - d2;
-
-//         assert_eq!(d3.data.x, -2.);
-//         assert_eq!(d3.data.y, -4.);
-//         assert_eq!(d3.data.z, -6.);
-
-//         // assert!(is_point(d3));
-//         assert!(is_direction(d3));
-//     }
-
-//     #[test]
-//     fn test_f32_mul_direction_or_vector() {
-//         let d = Direction::new(1., 2., 3.);
-//         let d2 = d * 2.;
-
-//         assert_eq!(d2.data.x, 2.);
-//         assert_eq!(d2.data.y, 4.);
-//         assert_eq!(d2.data.z, 6.);
-
-//         assert!(is_direction(d2));
-//         // assert!(is_point(d2));
-//     }
-
-//     #[test]
-//     fn test_vector_mul_mat4_identity() {
-//         let v: Point = Vector::new(1., 2., 3.);
-//         let m = Mat4::IDENTITY;
-//         let v2 = m * v;
-
-//         assert_eq!(v2.data.x, 1.);
-//         assert_eq!(v2.data.y, 2.);
-//         assert_eq!(v2.data.z, 3.);
-
-//         assert!(is_point(v2));
-//         // assert!(is_point(v2));
-//     }
-// }
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointVector {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DirectionVector {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NormalVector {}
+
+/// A `Vec3` tagged with what it represents, so that points, directions and
+/// normals only combine and transform in ways that make geometric sense.
+/// Derefs to the raw `Vec3` for read-only math such as dot products.
+#[derive(Debug, Clone, Copy)]
+pub struct Vector<T> {
+    pub data: Vec3,
+    kind: PhantomData<T>,
+}
+
+pub type Point = Vector<PointVector>;
+pub type Direction = Vector<DirectionVector>;
+pub type Normal = Vector<NormalVector>;
+
+impl<T> Vector<T> {
+    pub const ZERO: Self = Self::from_vec3(Vec3::ZERO);
+
+    pub fn new(x: Float, y: Float, z: Float) -> Self {
+        Self::from_vec3(Vec3::new(x, y, z))
+    }
+
+    pub const fn from_vec3(data: Vec3) -> Self {
+        Self {
+            data,
+            kind: PhantomData,
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for Vector<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+    }
+}
+
+impl<T> Deref for Vector<T> {
+    type Target = Vec3;
+
+    fn deref(&self) -> &Vec3 {
+        &self.data
+    }
+}
+
+impl<T> From<Vector<T>> for Vec3 {
+    fn from(v: Vector<T>) -> Self {
+        v.data
+    }
+}
+
+impl<T: Scalable> Vector<T> {
+    pub const X: Self = Self::from_vec3(Vec3::X);
+    pub const Y: Self = Self::from_vec3(Vec3::Y);
+    pub const Z: Self = Self::from_vec3(Vec3::Z);
+}
+
+impl Direction {
+    pub fn normalize(self) -> Self {
+        Self::from_vec3(self.data.normalize())
+    }
+
+    pub fn cross(self, rhs: Direction) -> Self {
+        Self::from_vec3(self.data.cross(rhs.data))
+    }
+}
+
+impl From<Normal> for Direction {
+    fn from(n: Normal) -> Self {
+        Self::from_vec3(n.data)
+    }
+}
+
+impl Normal {
+    pub fn normalize(self) -> Self {
+        Self::from_vec3(self.data.normalize())
+    }
+
+    /// This normal flipped, if needed, into the hemisphere around `v`.
+    pub fn face_forward(self, v: Vec3) -> Self {
+        if self.data.dot(v) < 0. {
+            -self
+        } else {
+            self
+        }
+    }
+}
+
+impl Add<Direction> for Point {
+    type Output = Point;
+
+    fn add(self, rhs: Direction) -> Self::Output {
+        Point::from_vec3(self.data + rhs.data)
+    }
+}
+
+impl AddAssign<Direction> for Point {
+    fn add_assign(&mut self, rhs: Direction) {
+        self.data += rhs.data;
+    }
+}
+
+impl Sub<Direction> for Point {
+    type Output = Point;
+
+    fn sub(self, rhs: Direction) -> Self::Output {
+        Point::from_vec3(self.data - rhs.data)
+    }
+}
+
+impl Sub<Point> for Point {
+    type Output = Direction;
+
+    fn sub(self, rhs: Point) -> Self::Output {
+        Direction::from_vec3(self.data - rhs.data)
+    }
+}
+
+impl Add<Direction> for Direction {
+    type Output = Direction;
+
+    fn add(self, rhs: Direction) -> Self::Output {
+        Direction::from_vec3(self.data + rhs.data)
+    }
+}
+
+impl Sub<Direction> for Direction {
+    type Output = Direction;
+
+    fn sub(self, rhs: Direction) -> Self::Output {
+        Direction::from_vec3(self.data - rhs.data)
+    }
+}
+
+impl<T: Scalable> Mul<Float> for Vector<T> {
+    type Output = Vector<T>;
+
+    fn mul(self, rhs: Float) -> Self::Output {
+        Vector::from_vec3(self.data * rhs)
+    }
+}
+
+impl<T: Scalable> Mul<Vector<T>> for Float {
+    type Output = Vector<T>;
+
+    fn mul(self, rhs: Vector<T>) -> Self::Output {
+        rhs * self
+    }
+}
+
+impl<T: Scalable> Div<Float> for Vector<T> {
+    type Output = Vector<T>;
+
+    fn div(self, rhs: Float) -> Self::Output {
+        Vector::from_vec3(self.data / rhs)
+    }
+}
+
+impl<T: Scalable> Neg for Vector<T> {
+    type Output = Vector<T>;
+
+    fn neg(self) -> Self::Output {
+        Vector::from_vec3(-self.data)
+    }
+}
+
+/// Kinds that can be scaled and negated; points cannot.
+pub trait Scalable {}
+
+impl Scalable for DirectionVector {}
+impl Scalable for NormalVector {}
+
+impl Mul<Point> for Mat4 {
+    type Output = Point;
+
+    fn mul(self, other: Point) -> Self::Output {
+        Point::from_vec3(self.transform_point3(other.data))
+    }
+}
+
+impl Mul<Direction> for Mat4 {
+    type Output = Direction;
+
+    fn mul(self, other: Direction) -> Self::Output {
+        Direction::from_vec3(self.transform_vector3(other.data))
+    }
+}
+
+/// Normals transform by the inverse transpose so they stay perpendicular to
+/// the surface under non-uniform scaling; the result is not renormalized.
+impl Mul<Normal> for Mat4 {
+    type Output = Normal;
+
+    fn mul(self, other: Normal) -> Self::Output {
+        Normal::from_vec3(self.inverse().transpose().transform_vector3(other.data))
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+
+    use crate::raytracer::math::Mat4;
+
+    use super::*;
+
+    fn is_point(vector: Point) -> bool {
+        vector.kind == PhantomData::<PointVector>
+    }
+
+    fn is_direction(vector: Direction) -> bool {
+        vector.kind == PhantomData::<DirectionVector>
+    }
+
+    #[test]
+    fn test_point() {
+        let p = Point::new(1.0, 2.0, 3.0);
+        assert_eq!(p.data.x, 1.0);
+        assert_eq!(p.data.y, 2.0);
+        assert_eq!(p.data.z, 3.0);
+    }
+
+    #[test]
+    fn test_point_add_direction() {
+        let p = Point::new(1.0, 2.0, 3.0);
+        let d = Direction::new(1.0, 2.0, 3.0);
+        let p2 = p + d;
+
+        assert_eq!(p2.data.x, 2.0);
+        assert!(is_point(p2));
+    }
+
+    #[test]
+    fn test_direction_point_sub() {
+        let p = Point::new(3., 2., 1.);
+        let d = Direction::new(5., 6., 7.);
+        let p2 = p - d;
+
+        assert_eq!(p2.data.x, -2.);
+        assert_eq!(p2.data.y, -4.);
+        assert_eq!(p2.data.z, -6.);
+
+        assert!(is_point(p2));
+    }
+
+    #[test]
+    fn test_point_point_sub() {
+        let p1 = Point::new(3., 2., 1.);
+        let p2 = Point::new(5., 6., 7.);
+        let d = p1 - p2;
+
+        assert_eq!(d, Direction::new(-2., -4., -6.));
+        assert!(is_direction(d));
+    }
+
+    #[test]
+    fn test_direction_direction_sub() {
+        let d1 = Direction::new(3., 2., 1.);
+        let d2 = Direction::new(5., 6., 7.);
+        let d3 = d1 - d2;
+
+        assert_eq!(d3.data.x, -2.);
+        assert_eq!(d3.data.y, -4.);
+        assert_eq!(d3.data.z, -6.);
+
+        assert!(is_direction(d3));
+    }
+
+    #[test]
+    fn test_float_mul_direction_or_vector() {
+        let d = Direction::new(1., 2., 3.);
+        let d2 = d * 2.;
+
+        assert_eq!(d2.data.x, 2.);
+        assert_eq!(d2.data.y, 4.);
+        assert_eq!(d2.data.z, 6.);
+
+        assert!(is_direction(d2));
+    }
+
+    #[test]
+    fn test_vector_mul_mat4_identity() {
+        let v: Point = Vector::new(1., 2., 3.);
+        let m = Mat4::IDENTITY;
+        let v2 = m * v;
+
+        assert_eq!(v2.data.x, 1.);
+        assert_eq!(v2.data.y, 2.);
+        assert_eq!(v2.data.z, 3.);
+
+        assert!(is_point(v2));
+    }
+
+    #[test]
+    fn test_spawned_rays_do_not_hit_their_origin() {
+        use crate::raytracer::{
+            hit_info::Hittable, material::MaterialKind, radiance::Rgb, sphere::Sphere,
+        };
+
+        let material = MaterialKind::Lambertian {
+            albedo: Rgb::splat(0.5),
+        };
+        // far from the origin, where a fixed epsilon is far below one ulp
+        let sphere = Sphere::new(Point::new(3e4, -2e4, -5e4), 1e3, material);
+        for i in 0..256 {
+            let target = sphere.center + Direction::from_vec3(random_unit_vector() * 500.);
+            let origin = Point::new(0., 0., i as Float);
+            let ray = Ray::new(origin, target - origin);
+            let hit = sphere.hit(&ray, 0., Float::INFINITY).unwrap();
+
+            let w = Direction::from_vec3(random_in_hemisphere(*hit.normal));
+            let spawned = hit.spawn_ray(w, 0.);
+            assert!(sphere.hit(&spawned, 0., Float::INFINITY).is_none());
+        }
+    }
+
+    #[test]
+    fn test_mat4_transform_rules() {
+        let m =
+            Mat4::from_translation(Vec3::new(1., 2., 3.)) * Mat4::from_scale(Vec3::new(2., 1., 1.));
+
+        assert_eq!(m * Point::new(1., 1., 1.), Point::new(3., 3., 4.));
+        assert_eq!(m * Direction::new(1., 1., 1.), Direction::new(2., 1., 1.));
+
+        // the plane x = y stretched along x keeps a normal perpendicular to it
+        let n = m * Normal::new(1., -1., 0.);
+        let tangent = m * Direction::new(1., 1., 0.);
+        assert!(n.dot(*tangent).abs() < 1e-6);
+    }
+}
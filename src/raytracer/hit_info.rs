@@ -1,24 +1,87 @@
-use std::rc::Rc;
-
-use macroquad::prelude::{Color, Vec3};
-
 use super::{
-    material::{Material, MaterialKind},
+    aabb::Aabb,
+    geometry::{offset_ray_origin, orthonormal_basis, Direction, Normal, Point},
+    material::MaterialKind,
+    math::{Float, Vec3},
     ray::Ray,
+    spectrum::SampledWavelengths,
 };
 
 pub trait Hittable {
-    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitInfo>;
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitInfo>;
+
+    /// Bounds of the object, or `None` if it is unbounded.
+    fn bounding_box(&self) -> Option<Aabb>;
+
+    /// Fraction of light a participating medium lets through between `t_min`
+    /// and `t_max`, or `None` for surfaces, which either block a ray or not.
+    fn transmittance(&self, _ray: &Ray, _t_min: Float, _t_max: Float) -> Option<Float> {
+        None
+    }
+}
+
+/// Successive hits of a ray on one object, nearest first. After each crossing
+/// the search continues from just past the surface, with `t` mapped back to the
+/// original ray, which the restarted segments share the direction of.
+pub struct Crossings<'a, H: ?Sized> {
+    object: &'a H,
+    ray: Ray,
+    segment: Ray,
+    t_start: Float,
+    t_min: Float,
+    t_max: Float,
+}
+
+impl<'a, H: Hittable + ?Sized> Crossings<'a, H> {
+    pub fn new(object: &'a H, ray: &Ray, t_min: Float, t_max: Float) -> Self {
+        Self {
+            object,
+            ray: *ray,
+            segment: *ray,
+            t_start: 0.,
+            t_min,
+            t_max,
+        }
+    }
+}
+
+impl<H: Hittable + ?Sized> Iterator for Crossings<'_, H> {
+    type Item = HitInfo;
+
+    fn next(&mut self) -> Option<HitInfo> {
+        let mut info = self
+            .object
+            .hit(&self.segment, self.t_min, self.t_max - self.t_start)?;
+        info.t += self.t_start;
+        self.segment = info.spawn_ray(self.ray.direction, self.ray.time);
+        self.t_start = info.t;
+        self.t_min = 0.;
+        Some(info)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct HitInfo {
     pub hit: bool,
-    pub t: f32,
-    pub p: Vec3,
-    pub normal: Vec3,
+    pub t: Float,
+    pub p: Point,
+    /// conservative bound on the absolute floating-point error of `p`
+    pub p_error: Vec3,
+    /// geometric normal, facing the incoming ray
+    pub normal: Normal,
+    /// normal used for shading, possibly perturbed by normal or bump maps;
+    /// always in the same hemisphere as `normal`
+    pub shading_normal: Normal,
+    /// partial derivatives of the surface position with respect to `u` and `v`
+    pub dpdu: Direction,
+    pub dpdv: Direction,
     pub front_face: bool,
     pub material: MaterialKind,
+    /// wavelengths carried by the path in spectral mode
+    pub wavelengths: Option<SampledWavelengths>,
+    /// surface texture coordinates
+    pub u: Float,
+    pub v: Float,
 }
 
 // trait AA {}
@@ -34,23 +97,60 @@ pub struct HitInfo {
 // }
 
 impl HitInfo {
-    pub fn new(hit: bool, t: f32, p: Vec3, normal: Vec3, material: MaterialKind) -> Self {
+    pub fn new(
+        hit: bool,
+        t: Float,
+        p: Point,
+        p_error: Vec3,
+        normal: Normal,
+        material: MaterialKind,
+    ) -> Self {
+        let (dpdu, dpdv) = orthonormal_basis(*normal);
         Self {
             hit,
             t,
             p,
+            p_error,
             normal,
+            shading_normal: normal,
+            dpdu: Direction::from_vec3(dpdu),
+            dpdv: Direction::from_vec3(dpdv),
             front_face: false,
             material,
+            wavelengths: None,
+            u: 0.,
+            v: 0.,
         }
     }
 
-    pub fn set_face_normal(&mut self, direction: Vec3, outward_normal: Vec3) {
-        self.front_face = direction.dot(outward_normal) < 0.;
+    pub fn set_uv(&mut self, u: Float, v: Float) {
+        self.u = u;
+        self.v = v;
+    }
+
+    /// Sets the surface tangents for primitives with a real parameterization.
+    pub fn set_tangents(&mut self, dpdu: Direction, dpdv: Direction) {
+        self.dpdu = dpdu;
+        self.dpdv = dpdv;
+    }
+
+    /// Ray leaving the surface towards `w`, starting just far enough off it
+    /// that it cannot hit the surface again.
+    pub fn spawn_ray(&self, w: Direction, time: Float) -> Ray {
+        Ray::with_time(
+            offset_ray_origin(self.p, self.p_error, self.normal, w),
+            w,
+            time,
+        )
+    }
+
+    pub fn set_face_normal(&mut self, direction: Direction, outward_normal: Normal) {
+        self.front_face = direction.dot(*outward_normal) < 0.;
         self.normal = if self.front_face {
             outward_normal
         } else {
             -outward_normal
         };
+        self.shading_normal = self.normal;
     }
 }
@@ -0,0 +1,292 @@
+use quad_rand::RandomRange;
+
+use super::{
+    camera::Camera,
+    film::{Film, Filter},
+    math::Float,
+    radiance::Rgb,
+    ray::Ray,
+    sampler::{PixelStats, SamplingMode},
+    spectrum::SampledWavelengths,
+    world::World,
+};
+
+/// Bounces after which paths are terminated with Russian roulette.
+pub const ROULETTE_DEPTH: u32 = 3;
+
+/// Maximum number of bounces of each kind along a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaxDepth {
+    /// non-specular reflection, including glossy lobes and volume scattering
+    pub diffuse: u32,
+    /// reflection off a perfectly smooth surface
+    pub specular: u32,
+    /// any bounce that passes through the surface
+    pub transmission: u32,
+}
+
+impl Default for MaxDepth {
+    fn default() -> Self {
+        Self {
+            diffuse: 16,
+            specular: 32,
+            transmission: 32,
+        }
+    }
+}
+
+/// Radiance along `ray`, in RGB or, if `wavelengths` is set, at the sampled wavelengths.
+pub fn get_ray_color(
+    ray: &Ray,
+    world: &World,
+    max_depth: MaxDepth,
+    wavelengths: Option<SampledWavelengths>,
+) -> Rgb {
+    let mut radiance = Rgb::black();
+    let mut throughput = Rgb::white();
+    let mut ray = *ray;
+    let mut depth = MaxDepth {
+        diffuse: 0,
+        specular: 0,
+        transmission: 0,
+    };
+
+    for bounces in 0.. {
+        let Some((object, mut hit)) = world.hit_object(&ray, 0., Float::INFINITY) else {
+            let sky = sky(&ray);
+            let sky = match wavelengths {
+                Some(wavelengths) => wavelengths.upsample(sky),
+                None => sky,
+            };
+            return radiance + throughput * sky;
+        };
+
+        hit.wavelengths = wavelengths;
+        let wo = -*ray.direction.normalize();
+        radiance += throughput * hit.material.emitted(wo, &hit);
+        let Some(sample) = hit.material.sample(wo, &hit) else {
+            break;
+        };
+
+        let transmitted = sample.wi.dot(*hit.normal) * wo.dot(*hit.normal) < 0.;
+        let (count, limit) = if transmitted && !hit.material.is_volumetric() {
+            (&mut depth.transmission, max_depth.transmission)
+        } else if sample.delta {
+            (&mut depth.specular, max_depth.specular)
+        } else {
+            (&mut depth.diffuse, max_depth.diffuse)
+        };
+        *count += 1;
+        if *count > limit {
+            break;
+        }
+
+        let Some((next, weight)) = sample.continue_path(object, &hit, ray.time) else {
+            break;
+        };
+        throughput *= hit.material.weight(&sample, &hit) * weight;
+        ray = next;
+
+        if throughput.is_black() {
+            break;
+        }
+        // only paths that have lost energy are terminated, and the survivors
+        // are reweighted so the estimate stays unbiased
+        let max = throughput.max_component();
+        if bounces >= ROULETTE_DEPTH && max < 1. {
+            let q = (1. - max).max(0.05);
+            if RandomRange::gen_range(0., 1.) < q {
+                break;
+            }
+            throughput = throughput / (1. - q);
+        }
+    }
+
+    radiance
+}
+
+/// Vertical white-to-blue gradient seen by rays that escape the scene.
+pub fn sky(ray: &Ray) -> Rgb {
+    let unit_direction = ray.direction.normalize();
+    let t = 0.5 * (unit_direction.y + 1.0);
+    let white = Rgb::new(1.0, 1.0, 1.0);
+    let blue = Rgb::new(0.5, 0.7, 1.0);
+
+    white.lerp(blue, t)
+}
+
+/// Traces samples through pixel `(x, y)` of a `width` by `height` image until
+/// `sampling` is satisfied, splatting them into `film`.
+#[allow(clippy::too_many_arguments)]
+pub fn render_pixel(
+    camera: &Camera,
+    world: &World,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    sampling: SamplingMode,
+    max_depth: MaxDepth,
+    spectral: bool,
+    film: &mut Film,
+) -> PixelStats {
+    let mut stats = PixelStats::new();
+    while sampling.needs_more_samples(&stats) {
+        let px = x as Float + RandomRange::gen_range(0., 1.);
+        let py = y as Float + RandomRange::gen_range(0., 1.);
+        let ray = Ray::from_camera(camera, px / width as Float, py / height as Float);
+        let color = if spectral {
+            let wavelengths = SampledWavelengths::random();
+            wavelengths.to_rgb(get_ray_color(&ray, world, max_depth, Some(wavelengths)))
+        } else {
+            get_ray_color(&ray, world, max_depth, None)
+        };
+        // a single NaN or infinite sample would poison the whole pixel
+        let color = if color.is_finite() {
+            color
+        } else {
+            Rgb::black()
+        };
+        stats.add(color);
+        film.add_sample(px, py, color);
+    }
+    stats
+}
+
+/// Renders `world` into a new film without touching any window or GPU state.
+#[allow(clippy::too_many_arguments)]
+pub fn render(
+    camera: &Camera,
+    world: &World,
+    width: usize,
+    height: usize,
+    sampling: SamplingMode,
+    max_depth: MaxDepth,
+    filter: Filter,
+    spectral: bool,
+) -> Film {
+    let mut film = Film::new(width, height, filter);
+    for x in 0..width {
+        for y in 0..height {
+            render_pixel(
+                camera, world, x, y, width, height, sampling, max_depth, spectral, &mut film,
+            );
+        }
+    }
+    film
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::{Float, Vec3};
+
+    use crate::raytracer::{
+        camera::Camera,
+        film::Filter,
+        geometry::{Direction, Point},
+        material::MaterialKind,
+        radiance::Rgb,
+        ray::Ray,
+        sampler::SamplingMode,
+        sphere::Sphere,
+        world::World,
+    };
+
+    use super::{get_ray_color, render, sky, MaxDepth};
+
+    #[test]
+    fn test_headless_render() {
+        let camera = Camera::with_resolution(Vec3::ZERO, 8, 4, 1.);
+        let mut world = World::new();
+        world.add(Box::new(Sphere {
+            center: Point::new(0., 0., -1.),
+            radius: 0.5,
+            material: MaterialKind::Lambertian {
+                albedo: Rgb::new(0.5, 0.5, 0.5),
+            },
+        }));
+
+        let film = render(
+            &camera,
+            &world,
+            8,
+            4,
+            SamplingMode::default(),
+            MaxDepth::default(),
+            Filter::default(),
+            false,
+        );
+        for x in 0..8 {
+            for y in 0..4 {
+                let pixel = film.pixel(x, y);
+                assert!(pixel.is_finite() && !pixel.is_black());
+            }
+        }
+    }
+
+    #[test]
+    fn test_max_depth_per_bounce_kind() {
+        let mut world = World::new();
+        world.add(Box::new(Sphere::new(
+            Point::new(0., 0., -1.),
+            0.5,
+            MaterialKind::Metal {
+                albedo: Rgb::splat(0.5),
+            },
+        )));
+
+        // straight back off the mirror into the sky
+        let ray = Ray::new(Point::new(0., 0., 0.), Direction::new(0., 0., -1.));
+        let escaped = Ray::new(Point::new(0., 0., -0.5), Direction::new(0., 0., 1.));
+        let expected = sky(&escaped) * 0.5;
+
+        let color = get_ray_color(&ray, &world, MaxDepth::default(), None);
+        assert!((color - expected).map(Float::abs).max_component() < 1e-4);
+
+        let no_specular = MaxDepth {
+            specular: 0,
+            ..MaxDepth::default()
+        };
+        assert!(get_ray_color(&ray, &world, no_specular, None).is_black());
+
+        let no_diffuse = MaxDepth {
+            diffuse: 0,
+            ..MaxDepth::default()
+        };
+        let color = get_ray_color(&ray, &world, no_diffuse, None);
+        assert!((color - expected).map(Float::abs).max_component() < 1e-4);
+    }
+
+    #[test]
+    fn test_closed_furnace_converges_with_roulette() {
+        // inside a closed sphere that reflects a fraction `a` of the light and
+        // emits `e` everywhere, radiance is e / (1 - a) in every direction
+        let (albedo, emission) = (0.8, 0.1);
+        let material = MaterialKind::DiffuseLight {
+            albedo: Rgb::splat(albedo),
+            emission: Rgb::splat(emission),
+        };
+        let mut world = World::new();
+        world.add(Box::new(Sphere::new(Point::ZERO, 1., material)));
+
+        let max_depth = MaxDepth {
+            diffuse: 1024,
+            ..MaxDepth::default()
+        };
+        let n = 20000;
+        let mut sum = 0.;
+        for i in 0..n {
+            let phi = i as Float;
+            let ray = Ray::new(Point::ZERO, Direction::new(phi.cos(), 0.3, phi.sin()));
+            sum += get_ray_color(&ray, &world, max_depth, None).g;
+        }
+
+        let expected = emission / (1. - albedo);
+        let estimate = sum / n as Float;
+        assert!(
+            (estimate - expected).abs() < 0.02,
+            "{estimate} vs {expected}"
+        );
+    }
+}
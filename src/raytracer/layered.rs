@@ -0,0 +1,152 @@
+use quad_rand::RandomRange;
+
+use super::{
+    bsdf::{BsdfSample, ShadingFrame},
+    math::{Float, Vec2, Vec3},
+    microfacet::{fresnel_dielectric, reflect, TrowbridgeReitz},
+    radiance::Rgb,
+};
+
+/// Thin dielectric layer, such as lacquer or varnish, on top of another material.
+///
+/// The coat reflects specularly according to the dielectric Fresnel term and the
+/// base only receives and returns what the coat transmits, so the layered material
+/// never reflects more energy than the base alone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coating {
+    pub ior: Float,
+    pub roughness: Float,
+    /// color of the light after crossing a layer of unit thickness straight on
+    pub tint: Rgb,
+    /// 0 is a perfectly clear coat
+    pub thickness: Float,
+}
+
+impl Default for Coating {
+    fn default() -> Self {
+        Self {
+            ior: 1.5,
+            roughness: 0.,
+            tint: Rgb::white(),
+            thickness: 0.,
+        }
+    }
+}
+
+impl Coating {
+    pub fn new(ior: Float, roughness: Float) -> Self {
+        Self {
+            ior,
+            roughness,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_tint(mut self, tint: Rgb, thickness: Float) -> Self {
+        self.tint = tint;
+        self.thickness = thickness;
+        self
+    }
+
+    fn distribution(&self) -> TrowbridgeReitz {
+        TrowbridgeReitz::from_roughness(self.roughness)
+    }
+
+    pub fn is_smooth(&self) -> bool {
+        self.distribution().is_smooth()
+    }
+
+    /// Probability of sampling the coat rather than the base for outgoing `wo`.
+    pub fn sample_probability(&self, wo: Vec3, n: Vec3) -> Float {
+        fresnel_dielectric(wo.dot(n).abs(), self.ior).clamp(0.1, 0.9)
+    }
+
+    /// Reflection of the coat alone, excluding the delta lobe of smooth coats.
+    pub fn eval(&self, wo: Vec3, wi: Vec3, n: Vec3) -> Rgb {
+        let frame = ShadingFrame::new(n);
+        let (wo, wi) = (frame.to_local(wo), frame.to_local(wi));
+        let distribution = self.distribution();
+        if distribution.is_smooth() || wo.z <= 0. || wi.z <= 0. {
+            return Rgb::black();
+        }
+
+        let wm = (wo + wi).normalize();
+        let f = fresnel_dielectric(wo.dot(wm), self.ior);
+        let s = distribution.d(wm) * distribution.g(wo, wi) * f / (4. * wo.z * wi.z);
+        Rgb::splat(s)
+    }
+
+    pub fn pdf(&self, wo: Vec3, wi: Vec3, n: Vec3) -> Float {
+        let frame = ShadingFrame::new(n);
+        let (wo, wi) = (frame.to_local(wo), frame.to_local(wi));
+        let distribution = self.distribution();
+        if distribution.is_smooth() || wo.z <= 0. || wi.z <= 0. {
+            return 0.;
+        }
+
+        let wm = (wo + wi).normalize();
+        distribution.d_visible(wo, wm) / (4. * wo.dot(wm))
+    }
+
+    pub fn sample(&self, wo: Vec3, n: Vec3) -> Option<BsdfSample> {
+        let frame = ShadingFrame::new(n);
+        let wo_local = frame.to_local(wo);
+        if wo_local.z <= 0. {
+            return None;
+        }
+
+        let distribution = self.distribution();
+        if distribution.is_smooth() {
+            let f = fresnel_dielectric(wo_local.z, self.ior);
+            return Some(BsdfSample {
+                wi: frame.to_world(Vec3::new(-wo_local.x, -wo_local.y, wo_local.z)),
+                f: Rgb::splat(f / wo_local.z),
+                pdf: 1.,
+                delta: true,
+                subsurface: None,
+            });
+        }
+
+        let u = Vec2::new(
+            RandomRange::gen_range(0., 1.),
+            RandomRange::gen_range(0., 1.),
+        );
+        let wm = distribution.sample_wm(wo_local, u);
+        let wi = frame.to_world(reflect(wo_local, wm));
+        let pdf = self.pdf(wo, wi, n);
+        if pdf <= 0. {
+            return None;
+        }
+        Some(BsdfSample {
+            wi,
+            f: self.eval(wo, wi, n),
+            pdf,
+            delta: false,
+            subsurface: None,
+        })
+    }
+
+    /// Fraction of light reaching the base along `wi` and leaving along `wo`:
+    /// Fresnel transmission through the interface twice and absorption inside the coat.
+    pub fn transmission(&self, wo: Vec3, wi: Vec3, n: Vec3) -> Rgb {
+        self.transmission_once(wo, n) * self.transmission_once(wi, n)
+    }
+
+    /// Fraction of light crossing the coat once along `w`, e.g. light emitted by the base.
+    pub fn transmission_once(&self, w: Vec3, n: Vec3) -> Rgb {
+        let cos = w.dot(n).abs();
+        let t = 1. - fresnel_dielectric(cos, self.ior);
+        if self.thickness <= 0. {
+            return Rgb::splat(t);
+        }
+
+        // path length inside the coat follows the refracted direction
+        let path = self.thickness / self.refracted_cos(cos);
+        self.tint.map(|c| c.max(1e-4).powf(path)) * t
+    }
+
+    fn refracted_cos(&self, cos: Float) -> Float {
+        let sin2 = (1. - cos * cos) / (self.ior * self.ior);
+        (1. - sin2).max(1e-4).sqrt()
+    }
+}
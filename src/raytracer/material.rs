@@ -1,87 +1,730 @@
-use std::fmt::Debug;
+use std::rc::Rc;
 
-use macroquad::prelude::{Color, Vec3};
+use quad_rand::RandomRange;
 
 use super::{
-    geometry::{near_zero, random_in_hemisphere, random_unit_vector, reflect},
+    alpha::AlphaMask,
+    bsdf::{BsdfSample, ShadingFrame},
+    bump::Bump,
+    conductor::Conductor,
+    dielectric::Dielectric,
+    geometry::{orthonormal_basis, random_unit_vector},
     hit_info::HitInfo,
-    ray::Ray,
+    layered::Coating,
+    math::{consts::PI, Float, Vec2, Vec3},
+    principled::{cosine_hemisphere, Principled},
+    radiance::Rgb,
+    spectrum::terminate_secondary,
+    subsurface::Subsurface,
+    texture::Texture,
 };
 
-pub trait Material: Debug {
-    fn scatter(&self, ray_in: &Ray, hit_info: &HitInfo) -> (Color, Ray);
+/// Surface or volume scattering model of an object.
+///
+/// All directions are in world space and point away from the hit point:
+/// `wo` towards the viewer, `wi` towards the light. `eval` returns the BSDF
+/// (or phase function) value without the cosine term, `pdf` the solid angle
+/// density `sample` would produce `wi` with. Delta lobes are never returned by
+/// `eval`/`pdf` and are flagged on the [`BsdfSample`] instead.
+#[derive(Debug, Clone)]
+pub enum MaterialKind {
+    Lambertian {
+        albedo: Rgb,
+    },
+    /// Perfect mirror.
+    Metal {
+        albedo: Rgb,
+    },
+    /// Lambertian whose albedo is looked up from a texture at the hit UVs.
+    TexturedLambertian {
+        albedo: Texture,
+    },
+    /// Lambertian that also emits `emission` from both sides, e.g. an area light.
+    DiffuseLight {
+        albedo: Rgb,
+        emission: Rgb,
+    },
+    /// Phase function of participating media: scatters uniformly in all directions.
+    Isotropic {
+        albedo: Rgb,
+    },
+    /// Henyey-Greenstein phase function with optional emission, used by heterogeneous media.
+    Medium {
+        albedo: Rgb,
+        g: Float,
+        emission: Rgb,
+    },
+    Principled(Principled),
+    /// GGX microfacet metal with complex IOR Fresnel.
+    Conductor(Conductor),
+    /// GGX microfacet glass.
+    Dielectric(Dielectric),
+    /// Any material with its shading normal perturbed by a normal or bump map.
+    Bumped {
+        material: Rc<MaterialKind>,
+        bump: Bump,
+    },
+    /// Translucent material whose interior is simulated with a random walk.
+    Subsurface(Subsurface),
+    /// Blend of two materials, picking `b` with probability given by the luminance of
+    /// `weight`. Bump maps should wrap the mix rather than its components.
+    Mix {
+        a: Rc<MaterialKind>,
+        b: Rc<MaterialKind>,
+        weight: Texture,
+    },
+    /// Dielectric coating over a base material, e.g. lacquered wood or car paint.
+    Layered {
+        base: Rc<MaterialKind>,
+        coat: Coating,
+    },
+    /// Any material with cut-out regions that rays pass through.
+    Masked {
+        material: Rc<MaterialKind>,
+        mask: AlphaMask,
+    },
 }
 
-#[derive(Debug)]
-pub struct LambertianMaterial {
-    albedo: Color,
+impl MaterialKind {
+    pub fn with_bump(self, bump: Bump) -> Self {
+        Self::Bumped {
+            material: Rc::new(self),
+            bump,
+        }
+    }
+
+    pub fn mix(a: MaterialKind, b: MaterialKind, weight: Texture) -> Self {
+        Self::Mix {
+            a: Rc::new(a),
+            b: Rc::new(b),
+            weight,
+        }
+    }
+
+    pub fn with_coating(self, coat: Coating) -> Self {
+        Self::Layered {
+            base: Rc::new(self),
+            coat,
+        }
+    }
+
+    pub fn with_alpha_mask(self, mask: AlphaMask) -> Self {
+        Self::Masked {
+            material: Rc::new(self),
+            mask,
+        }
+    }
+
+    /// Probability that a ray stops at the hit point rather than passing through a cut-out.
+    pub fn coverage(&self, hit_info: &HitInfo) -> Float {
+        match self {
+            Self::Masked { material, mask } => {
+                mask.coverage(hit_info) * material.coverage(hit_info)
+            }
+            Self::Bumped { material, .. } => material.coverage(hit_info),
+            Self::Layered { base, .. } => base.coverage(hit_info),
+            Self::Mix { a, b, weight } => {
+                let (a, b) = (a.coverage(hit_info), b.coverage(hit_info));
+                a + (b - a) * mix_weight(weight, hit_info)
+            }
+            _ => 1.,
+        }
+    }
+
+    /// True if the surface is cut out at the hit point and the hit should be ignored.
+    pub fn passes_through(&self, hit_info: &HitInfo) -> bool {
+        let coverage = self.coverage(hit_info);
+        coverage < 1. && RandomRange::gen_range(0., 1.) >= coverage
+    }
+
+    pub fn sample(&self, wo: Vec3, hit_info: &HitInfo) -> Option<BsdfSample> {
+        let sample = self.sample_raw(wo, hit_info)?;
+        Some(BsdfSample {
+            f: self.to_channels(sample.f, hit_info),
+            ..sample
+        })
+    }
+
+    pub fn eval(&self, wo: Vec3, wi: Vec3, hit_info: &HitInfo) -> Rgb {
+        self.to_channels(self.eval_raw(wo, wi, hit_info), hit_info)
+    }
+
+    /// Radiance emitted at the hit point.
+    pub fn emitted(&self, wo: Vec3, hit_info: &HitInfo) -> Rgb {
+        self.to_channels(self.emitted_raw(wo, hit_info), hit_info)
+    }
+
+    /// In spectral mode, converts RGB results of materials that are not
+    /// wavelength aware to the wavelengths carried by the path.
+    fn to_channels(&self, c: Rgb, hit_info: &HitInfo) -> Rgb {
+        if self.is_spectral() {
+            return c;
+        }
+        upsample(c, hit_info)
+    }
+
+    /// True if the material handles `HitInfo::wavelengths` itself.
+    fn is_spectral(&self) -> bool {
+        matches!(
+            self,
+            Self::Conductor(_)
+                | Self::Dielectric(_)
+                | Self::Subsurface(_)
+                | Self::Mix { .. }
+                | Self::Layered { .. }
+                | Self::Bumped { .. }
+                | Self::Masked { .. }
+        )
+    }
+
+    fn sample_raw(&self, wo: Vec3, hit_info: &HitInfo) -> Option<BsdfSample> {
+        let n = *hit_info.shading_normal;
+        match self {
+            Self::Lambertian { .. }
+            | Self::TexturedLambertian { .. }
+            | Self::DiffuseLight { .. } => {
+                let frame = ShadingFrame::new(n);
+                let wi = frame.to_world(cosine_hemisphere(random_vec2()));
+                let pdf = self.pdf(wo, wi, hit_info);
+                if pdf <= 0. {
+                    return None;
+                }
+                Some(BsdfSample {
+                    wi,
+                    f: self.eval_raw(wo, wi, hit_info),
+                    pdf,
+                    delta: false,
+                    subsurface: None,
+                })
+            }
+            Self::Metal { albedo } => {
+                let wi = 2. * wo.dot(n) * n - wo;
+                let cos = wi.dot(n);
+                if cos <= 0. {
+                    return None;
+                }
+                Some(BsdfSample {
+                    wi,
+                    f: *albedo / cos,
+                    pdf: 1.,
+                    delta: true,
+                    subsurface: None,
+                })
+            }
+            Self::Isotropic { .. } | Self::Medium { .. } => {
+                let wi = match self {
+                    Self::Medium { g, .. } => sample_henyey_greenstein(-wo, *g),
+                    _ => random_unit_vector(),
+                };
+                Some(BsdfSample {
+                    wi,
+                    f: self.eval_raw(wo, wi, hit_info),
+                    pdf: self.pdf(wo, wi, hit_info),
+                    delta: false,
+                    subsurface: None,
+                })
+            }
+            Self::Principled(principled) => {
+                principled.sample(wo, ShadingFrame::from_hit(hit_info), hit_info.front_face)
+            }
+            Self::Conductor(conductor) => {
+                conductor_at(conductor, hit_info).sample(wo, ShadingFrame::from_hit(hit_info))
+            }
+            Self::Dielectric(dielectric) => sample_dielectric(dielectric, wo, hit_info),
+            Self::Subsurface(subsurface) => {
+                let sample = sample_dielectric(&subsurface.surface, wo, hit_info)?;
+                if !hit_info.front_face || sample.wi.dot(*hit_info.normal) >= 0. {
+                    return Some(sample);
+                }
+                let subsurface = match hit_info.wavelengths {
+                    Some(wavelengths) => subsurface.at_wavelengths(&wavelengths),
+                    None => *subsurface,
+                };
+                Some(BsdfSample {
+                    subsurface: Some(subsurface),
+                    ..sample
+                })
+            }
+            Self::Bumped { material, bump } => material.sample(wo, &bump.apply(hit_info)),
+            Self::Masked { material, .. } => material.sample(wo, hit_info),
+            Self::Mix { a, b, weight } => {
+                let t = shading_mix_weight(a, b, weight, hit_info);
+                let (chosen, p) = if RandomRange::gen_range(0., 1.) < t {
+                    (b, t)
+                } else {
+                    (a, 1. - t)
+                };
+                let sample = chosen.sample(wo, hit_info)?;
+                if sample.delta {
+                    // the other material has no delta lobe in this exact direction
+                    return Some(BsdfSample {
+                        f: sample.f * p,
+                        pdf: sample.pdf * p,
+                        ..sample
+                    });
+                }
+                Some(BsdfSample {
+                    f: self.eval(wo, sample.wi, hit_info),
+                    pdf: self.pdf(wo, sample.wi, hit_info),
+                    ..sample
+                })
+            }
+            Self::Layered { base, coat } => {
+                let p_coat = coat.sample_probability(wo, n);
+                if RandomRange::gen_range(0., 1.) < p_coat {
+                    let sample = coat.sample(wo, n)?;
+                    if sample.delta {
+                        return Some(BsdfSample {
+                            pdf: sample.pdf * p_coat,
+                            ..sample
+                        });
+                    }
+                    return Some(BsdfSample {
+                        f: self.eval(wo, sample.wi, hit_info),
+                        pdf: self.pdf(wo, sample.wi, hit_info),
+                        ..sample
+                    });
+                }
+
+                let sample = base.sample(wo, hit_info)?;
+                if sample.delta {
+                    return Some(BsdfSample {
+                        f: sample.f * upsample(coat.transmission(wo, sample.wi, n), hit_info),
+                        pdf: sample.pdf * (1. - p_coat),
+                        ..sample
+                    });
+                }
+                Some(BsdfSample {
+                    f: self.eval(wo, sample.wi, hit_info),
+                    pdf: self.pdf(wo, sample.wi, hit_info),
+                    ..sample
+                })
+            }
+        }
+    }
+
+    fn eval_raw(&self, wo: Vec3, wi: Vec3, hit_info: &HitInfo) -> Rgb {
+        let n = *hit_info.shading_normal;
+        match self {
+            Self::Lambertian { albedo } | Self::DiffuseLight { albedo, .. } => {
+                lambertian(*albedo, wi, n)
+            }
+            Self::TexturedLambertian { albedo } => lambertian(
+                Rgb::from(albedo.value(hit_info.u, hit_info.v, hit_info.p)),
+                wi,
+                n,
+            ),
+            Self::Metal { .. } => Rgb::black(),
+            Self::Isotropic { albedo } => *albedo / (4. * PI),
+            Self::Medium { albedo, g, .. } => *albedo * henyey_greenstein(-wo.dot(wi), *g),
+            Self::Principled(principled) => principled.eval(
+                wo,
+                wi,
+                ShadingFrame::from_hit(hit_info),
+                hit_info.front_face,
+            ),
+            Self::Conductor(conductor) => {
+                conductor_at(conductor, hit_info).eval(wo, wi, ShadingFrame::from_hit(hit_info))
+            }
+            Self::Dielectric(dielectric) => eval_dielectric(dielectric, wo, wi, hit_info),
+            Self::Subsurface(subsurface) => eval_dielectric(&subsurface.surface, wo, wi, hit_info),
+            Self::Bumped { material, bump } => material.eval(wo, wi, &bump.apply(hit_info)),
+            Self::Masked { material, .. } => material.eval(wo, wi, hit_info),
+            Self::Mix { a, b, weight } => (a.eval(wo, wi, hit_info)).lerp(
+                b.eval(wo, wi, hit_info),
+                shading_mix_weight(a, b, weight, hit_info),
+            ),
+            Self::Layered { base, coat } => {
+                coat.eval(wo, wi, n)
+                    + base.eval(wo, wi, hit_info) * upsample(coat.transmission(wo, wi, n), hit_info)
+            }
+        }
+    }
+
+    pub fn pdf(&self, wo: Vec3, wi: Vec3, hit_info: &HitInfo) -> Float {
+        let n = *hit_info.shading_normal;
+        match self {
+            Self::Lambertian { .. }
+            | Self::TexturedLambertian { .. }
+            | Self::DiffuseLight { .. } => wi.dot(n).max(0.) / PI,
+            Self::Metal { .. } => 0.,
+            Self::Isotropic { .. } => 1. / (4. * PI),
+            Self::Medium { g, .. } => henyey_greenstein(-wo.dot(wi), *g),
+            Self::Principled(principled) => principled.pdf(
+                wo,
+                wi,
+                ShadingFrame::from_hit(hit_info),
+                hit_info.front_face,
+            ),
+            Self::Conductor(conductor) => conductor.pdf(wo, wi, ShadingFrame::from_hit(hit_info)),
+            Self::Dielectric(dielectric) => pdf_dielectric(dielectric, wo, wi, hit_info),
+            Self::Subsurface(subsurface) => pdf_dielectric(&subsurface.surface, wo, wi, hit_info),
+            Self::Bumped { material, bump } => material.pdf(wo, wi, &bump.apply(hit_info)),
+            Self::Masked { material, .. } => material.pdf(wo, wi, hit_info),
+            Self::Mix { a, b, weight } => {
+                let t = shading_mix_weight(a, b, weight, hit_info);
+                (1. - t) * a.pdf(wo, wi, hit_info) + t * b.pdf(wo, wi, hit_info)
+            }
+            Self::Layered { base, coat } => {
+                let p_coat = coat.sample_probability(wo, n);
+                p_coat * coat.pdf(wo, wi, n) + (1. - p_coat) * base.pdf(wo, wi, hit_info)
+            }
+        }
+    }
+
+    /// Path throughput `f * |cos| / pdf` of a sample returned by `sample`.
+    /// Phase functions have no cosine term.
+    pub fn weight(&self, sample: &BsdfSample, hit_info: &HitInfo) -> Rgb {
+        if let Self::Bumped { material, bump } = self {
+            return material.weight(sample, &bump.apply(hit_info));
+        }
+        if let Self::Masked { material, .. } = self {
+            return material.weight(sample, hit_info);
+        }
+        if self.is_volumetric() {
+            if sample.pdf <= 0. {
+                return Rgb::black();
+            }
+            return sample.f / sample.pdf;
+        }
+        sample.weight(*hit_info.shading_normal)
+    }
+
+    /// True if the material only has delta lobes, so `eval` and `pdf` are always zero
+    /// and light sampling is useless.
+    pub fn is_delta(&self) -> bool {
+        match self {
+            Self::Metal { .. } => true,
+            Self::Conductor(conductor) => conductor.is_smooth(),
+            Self::Dielectric(dielectric) => dielectric.is_smooth(),
+            Self::Subsurface(subsurface) => subsurface.surface.is_smooth(),
+            Self::Bumped { material, .. } | Self::Masked { material, .. } => material.is_delta(),
+            Self::Mix { a, b, .. } => a.is_delta() && b.is_delta(),
+            Self::Layered { base, coat } => coat.is_smooth() && base.is_delta(),
+            _ => false,
+        }
+    }
+
+    /// True for phase functions of participating media.
+    pub fn is_volumetric(&self) -> bool {
+        match self {
+            Self::Isotropic { .. } | Self::Medium { .. } => true,
+            Self::Bumped { material, .. } | Self::Masked { material, .. } => {
+                material.is_volumetric()
+            }
+            _ => false,
+        }
+    }
+
+    fn emitted_raw(&self, wo: Vec3, hit_info: &HitInfo) -> Rgb {
+        match self {
+            Self::DiffuseLight { emission, .. } => *emission,
+            // collision estimator: absorbed fraction (1 - albedo) of collisions emit
+            Self::Medium {
+                albedo, emission, ..
+            } => *emission * (Rgb::white() - *albedo),
+            Self::Bumped { material, bump } => material.emitted(wo, &bump.apply(hit_info)),
+            Self::Masked { material, .. } => material.emitted(wo, hit_info),
+            Self::Mix { a, b, weight } => a.emitted(wo, hit_info).lerp(
+                b.emitted(wo, hit_info),
+                shading_mix_weight(a, b, weight, hit_info),
+            ),
+            Self::Layered { base, coat } => {
+                let n = *hit_info.shading_normal;
+                base.emitted(wo, hit_info) * upsample(coat.transmission_once(wo, n), hit_info)
+            }
+            _ => Rgb::black(),
+        }
+    }
 }
 
-impl LambertianMaterial {
-    pub fn new(albedo: Color) -> Self {
-        Self { albedo }
+fn upsample(c: Rgb, hit_info: &HitInfo) -> Rgb {
+    match hit_info.wavelengths {
+        Some(wavelengths) => wavelengths.upsample(c),
+        None => c,
     }
 }
 
-impl Material for LambertianMaterial {
-    fn scatter(&self, _ray_in: &Ray, hit_info: &HitInfo) -> (Color, Ray) {
-        let mut scatter_direction = hit_info.normal + random_unit_vector();
+fn conductor_at(conductor: &Conductor, hit_info: &HitInfo) -> Conductor {
+    match hit_info.wavelengths {
+        Some(wavelengths) => conductor.at_wavelengths(&wavelengths),
+        None => *conductor,
+    }
+}
 
-        if near_zero(scatter_direction) {
-            scatter_direction = hit_info.normal;
-        }
+fn dielectric_at(dielectric: &Dielectric, hit_info: &HitInfo) -> Dielectric {
+    match hit_info.wavelengths {
+        Some(wavelengths) => dielectric.at_wavelengths(&wavelengths),
+        None => *dielectric,
+    }
+}
+
+/// Dispersion sends each wavelength in a different direction, so only the
+/// hero wavelength survives an interaction with a dispersive interface.
+fn dispersed(dielectric: &Dielectric, f: Rgb, hit_info: &HitInfo) -> Rgb {
+    if hit_info.wavelengths.is_some() && dielectric.is_dispersive() {
+        terminate_secondary(f)
+    } else {
+        f
+    }
+}
+
+fn sample_dielectric(dielectric: &Dielectric, wo: Vec3, hit_info: &HitInfo) -> Option<BsdfSample> {
+    let sample = dielectric_at(dielectric, hit_info).sample(
+        wo,
+        ShadingFrame::from_hit(hit_info),
+        hit_info.front_face,
+    )?;
+    Some(BsdfSample {
+        f: dispersed(dielectric, sample.f, hit_info),
+        ..sample
+    })
+}
 
-        let scattered = Ray::new(hit_info.p, scatter_direction);
+fn eval_dielectric(dielectric: &Dielectric, wo: Vec3, wi: Vec3, hit_info: &HitInfo) -> Rgb {
+    let f = dielectric_at(dielectric, hit_info).eval(
+        wo,
+        wi,
+        ShadingFrame::from_hit(hit_info),
+        hit_info.front_face,
+    );
+    dispersed(dielectric, f, hit_info)
+}
+
+fn pdf_dielectric(dielectric: &Dielectric, wo: Vec3, wi: Vec3, hit_info: &HitInfo) -> Float {
+    dielectric_at(dielectric, hit_info).pdf(
+        wo,
+        wi,
+        ShadingFrame::from_hit(hit_info),
+        hit_info.front_face,
+    )
+}
 
-        (self.albedo, scattered)
+fn mix_weight(weight: &Texture, hit_info: &HitInfo) -> Float {
+    Rgb::from(weight.value(hit_info.u, hit_info.v, hit_info.p))
+        .luminance()
+        .clamp(0., 1.)
+}
+
+/// Weight of `b` among the hits that were not cut out, so that shading picks
+/// the components in proportion to how often rays actually stop on them.
+fn shading_mix_weight(
+    a: &MaterialKind,
+    b: &MaterialKind,
+    weight: &Texture,
+    hit_info: &HitInfo,
+) -> Float {
+    let t = mix_weight(weight, hit_info);
+    let (a, b) = (a.coverage(hit_info), b.coverage(hit_info));
+    let kept = a + (b - a) * t;
+    if kept > 0. {
+        t * b / kept
+    } else {
+        t
     }
 }
 
-// #[derive(Debug)]
-// struct MetalMaterial {
-//     albedo: Color,
-// }
+fn lambertian(albedo: Rgb, wi: Vec3, n: Vec3) -> Rgb {
+    if wi.dot(n) <= 0. {
+        return Rgb::black();
+    }
+    albedo / PI
+}
 
-// impl Material for MetalMaterial {
-//     fn scatter(&self, ray_in: &Ray, hit_info: &HitInfo) -> Option<(Color, Ray)> {
-//         let reflected = ray_in.direction.normalize().reflect(hit_info.normal);
-//         let scattered = Ray::new(hit_info.p, reflected);
+fn random_vec2() -> Vec2 {
+    Vec2::new(
+        RandomRange::gen_range(0., 1.),
+        RandomRange::gen_range(0., 1.),
+    )
+}
 
-//         if scattered.direction.dot(hit_info.normal) > 0. {
-//             return Some((self.albedo, scattered));
-//         }
+/// Samples a direction around `forward` from the Henyey-Greenstein distribution.
+pub fn sample_henyey_greenstein(forward: Vec3, g: Float) -> Vec3 {
+    let xi = RandomRange::gen_range(0., 1.);
+    let cos_theta = if g.abs() < 1e-3 {
+        1. - 2. * xi
+    } else {
+        let s = (1. - g * g) / (1. - g + 2. * g * xi);
+        (1. + g * g - s * s) / (2. * g)
+    };
+    let sin_theta = (1. - cos_theta * cos_theta).max(0.).sqrt();
+    let phi = RandomRange::gen_range(0., 2. * PI);
 
-//         None
-//     }
-// }
+    let (t, b) = orthonormal_basis(forward);
+    t * (sin_theta * phi.cos()) + b * (sin_theta * phi.sin()) + forward * cos_theta
+}
 
-#[derive(Debug, Clone, Copy)]
-pub enum MaterialKind {
-    Lambertian { albedo: Color },
-    Metal { albedo: Color },
-    // Metal(MetalMaterial),
+/// Henyey-Greenstein phase function for the cosine between the
+/// propagation direction before and after scattering.
+pub fn henyey_greenstein(cos_theta: Float, g: Float) -> Float {
+    let denom = 1. + g * g - 2. * g * cos_theta;
+    (1. - g * g) / (4. * PI * denom * denom.max(1e-8).sqrt())
 }
 
-impl MaterialKind {
-    pub fn scatter(&self, ray_in: &Ray, hit_info: &HitInfo) -> (Color, Ray, bool) {
-        match self {
-            Self::Lambertian { albedo } => {
-                let mut scatter_direction = hit_info.normal + random_in_hemisphere(hit_info.normal);
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::{Float, Vec3};
 
-                if near_zero(scatter_direction) {
-                    scatter_direction = hit_info.normal;
-                }
+    use crate::raytracer::{
+        alpha::AlphaMask,
+        bump::Bump,
+        color::Color,
+        geometry::{Direction, Normal, Point},
+        hit_info::HitInfo,
+        layered::Coating,
+        material::MaterialKind,
+        radiance::Rgb,
+        subsurface::Subsurface,
+        texture::Texture,
+    };
 
-                let scattered = Ray::new(hit_info.p, scatter_direction);
+    #[test]
+    fn test_sample_matches_eval_and_pdf() {
+        let albedo = Rgb::new(0.5, 0.5, 0.5);
+        let wo = Vec3::new(0.3, 0.2, 1.).normalize();
+        for material in [
+            MaterialKind::Lambertian { albedo },
+            MaterialKind::Medium {
+                albedo,
+                g: 0.6,
+                emission: Rgb::black(),
+            },
+        ] {
+            let hit = HitInfo::new(
+                true,
+                1.,
+                Point::ZERO,
+                Vec3::ZERO,
+                Normal::Z,
+                material.clone(),
+            );
+            for _ in 0..32 {
+                let sample = material.sample(wo, &hit).unwrap();
+                assert!(!sample.delta);
+                assert!((material.pdf(wo, sample.wi, &hit) - sample.pdf).abs() < 1e-3);
+                assert!((material.eval(wo, sample.wi, &hit).r - sample.f.r).abs() < 1e-4);
+                // both are importance sampled exactly, so the weight is the albedo
+                assert!((material.weight(&sample, &hit).r - 0.5).abs() < 1e-3);
+            }
+        }
+    }
 
-                (*albedo, scattered, true)
-            } // Self::Metal(material) => material.scatter(ray_in, hit_info),
-            Self::Metal { albedo } => {
-                let reflected = reflect(ray_in.direction.normalize(), hit_info.normal);
-                let scattered = Ray::new(hit_info.p, reflected);
-                let ok = scattered.direction.dot(hit_info.normal) > 0.;
-                (*albedo, scattered, ok)
+    #[test]
+    fn test_layered_and_mix_conserve_energy() {
+        let white = MaterialKind::Lambertian {
+            albedo: Rgb::white(),
+        };
+        let metal = MaterialKind::Metal {
+            albedo: Rgb::white(),
+        };
+        let wo = Vec3::new(0.5, 0., 0.5).normalize();
+        for material in [
+            white.clone().with_coating(Coating::new(1.5, 0.)),
+            white.clone().with_coating(Coating::new(1.5, 0.3)),
+            MaterialKind::mix(white, metal, Texture::Solid(Color::new(0.5, 0.5, 0.5, 1.))),
+        ] {
+            let hit = HitInfo::new(
+                true,
+                1.,
+                Point::ZERO,
+                Vec3::ZERO,
+                Normal::Z,
+                material.clone(),
+            );
+            let n = 20000;
+            let mut albedo = 0.;
+            for _ in 0..n {
+                if let Some(sample) = material.sample(wo, &hit) {
+                    if !sample.delta {
+                        let pdf = material.pdf(wo, sample.wi, &hit);
+                        assert!((pdf - sample.pdf).abs() < 1e-3 * pdf.max(1.));
+                    }
+                    albedo += material.weight(&sample, &hit).g;
+                }
             }
+            let albedo = albedo / n as Float;
+            assert!(albedo > 0.5 && albedo < 1.02, "albedo {albedo}");
+        }
+    }
+
+    #[test]
+    fn test_wrappers_forward_emission_and_cut_outs() {
+        let glow = MaterialKind::Medium {
+            albedo: Rgb::black(),
+            g: 0.,
+            emission: Rgb::white(),
+        };
+        let coated = glow.with_coating(Coating::new(1.5, 0.));
+        let hit = HitInfo::new(true, 1., Point::ZERO, Vec3::ZERO, Normal::Z, coated.clone());
+        let emitted = coated.emitted(Vec3::Z, &hit).r;
+        // only the Fresnel reflection of the coat is lost
+        assert!((emitted - 0.96).abs() < 1e-3, "emitted {emitted}");
+
+        let solid = MaterialKind::Lambertian {
+            albedo: Rgb::white(),
+        };
+        let cut_out = solid
+            .clone()
+            .with_alpha_mask(AlphaMask::new(Texture::Solid(Color::new(1., 1., 1., 0.))));
+        for (weight, passes) in [(0., false), (1., true)] {
+            let mix = MaterialKind::mix(
+                solid.clone(),
+                cut_out.clone(),
+                Texture::Solid(Color::new(weight, weight, weight, 1.)),
+            );
+            let hit = HitInfo::new(true, 1., Point::ZERO, Vec3::ZERO, Normal::Z, mix.clone());
+            assert_eq!(mix.passes_through(&hit), passes);
+        }
+
+        // half the rays pass through, and those that stop only ever see the solid side
+        let mix = MaterialKind::mix(
+            solid.clone(),
+            cut_out.with_coating(Coating::new(1.5, 0.)),
+            Texture::Solid(Color::new(0.5, 0.5, 0.5, 1.)),
+        );
+        let hit = HitInfo::new(true, 1., Point::ZERO, Vec3::ZERO, Normal::Z, mix.clone());
+        assert!((mix.coverage(&hit) - 0.5).abs() < 1e-6);
+        let wi = Vec3::new(0.6, 0., 0.8);
+        let (f, expected) = (mix.eval(Vec3::Z, wi, &hit), solid.eval(Vec3::Z, wi, &hit));
+        assert!((f - expected).map(Float::abs).max_component() < 1e-6);
+    }
+
+    #[test]
+    fn test_wrapped_subsurface_still_walks() {
+        let skin = MaterialKind::Subsurface(Subsurface::new(Rgb::splat(0.8), Rgb::splat(0.1)));
+        let opaque = AlphaMask::new(Texture::Solid(Color::new(1., 1., 1., 1.)));
+        let half = Texture::Solid(Color::new(0.5, 0.5, 0.5, 1.));
+        let wo = Vec3::Z;
+        for material in [
+            skin.clone().with_alpha_mask(opaque),
+            skin.clone().with_bump(Bump::Height {
+                texture: half.clone(),
+                scale: 0.,
+            }),
+            skin.clone().with_coating(Coating::new(1.3, 0.)),
+            MaterialKind::mix(
+                skin.clone(),
+                MaterialKind::Lambertian {
+                    albedo: Rgb::white(),
+                },
+                half,
+            ),
+        ] {
+            let mut hit = HitInfo::new(
+                true,
+                1.,
+                Point::ZERO,
+                Vec3::ZERO,
+                Normal::Z,
+                material.clone(),
+            );
+            hit.set_face_normal(-Direction::Z, Normal::Z);
+            let walks = (0..200)
+                .filter_map(|_| material.sample(wo, &hit))
+                .filter(|sample| sample.subsurface.is_some())
+                .count();
+            assert!(walks > 0);
         }
     }
 }
@@ -0,0 +1,20 @@
+//! Scalar and vector types used throughout the core. The `f64` feature swaps
+//! them for double precision, for scenes where f32 runs out of bits.
+
+#[cfg(not(feature = "f64"))]
+mod precision {
+    pub use glam::{Mat4, Vec2, Vec3};
+    pub use std::f32::consts;
+
+    pub type Float = f32;
+}
+
+#[cfg(feature = "f64")]
+mod precision {
+    pub use glam::{DMat4 as Mat4, DVec2 as Vec2, DVec3 as Vec3};
+    pub use std::f64::consts;
+
+    pub type Float = f64;
+}
+
+pub use precision::*;
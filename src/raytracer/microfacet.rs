@@ -0,0 +1,500 @@
+//! Trowbridge-Reitz (GGX) microfacet model with Smith masking and
+//! visible normal sampling. All directions are in a local shading frame
+//! with the macro surface normal along +z.
+
+use super::radiance::Rgb;
+use super::{
+    bsdf::BsdfSample,
+    math::{consts::PI, Float, Vec2, Vec3},
+};
+
+/// Below this alpha a surface is treated as perfectly smooth (a delta lobe).
+pub const SMOOTH_ALPHA: Float = 1e-3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrowbridgeReitz {
+    pub alpha_x: Float,
+    pub alpha_y: Float,
+}
+
+impl TrowbridgeReitz {
+    pub fn new(alpha_x: Float, alpha_y: Float) -> Self {
+        Self { alpha_x, alpha_y }
+    }
+
+    /// Isotropic distribution with the usual `alpha = roughness^2` remapping.
+    pub fn from_roughness(roughness: Float) -> Self {
+        let alpha = roughness * roughness;
+        Self::new(alpha, alpha)
+    }
+
+    /// Anisotropic distribution stretched along the shading tangent for
+    /// `anisotropy > 0`, using the Disney parameterization.
+    pub fn from_roughness_anisotropic(roughness: Float, anisotropy: Float) -> Self {
+        let alpha = roughness * roughness;
+        let aspect = (1. - 0.9 * anisotropy.clamp(0., 1.)).sqrt();
+        Self::new(alpha / aspect, alpha * aspect)
+    }
+
+    pub fn is_smooth(&self) -> bool {
+        self.alpha_x.max(self.alpha_y) < SMOOTH_ALPHA
+    }
+
+    /// Distribution of microfacet normals.
+    pub fn d(&self, wm: Vec3) -> Float {
+        if wm.z <= 0. {
+            return 0.;
+        }
+        let x = wm.x / self.alpha_x;
+        let y = wm.y / self.alpha_y;
+        let d = x * x + y * y + wm.z * wm.z;
+        1. / (PI * self.alpha_x * self.alpha_y * d * d)
+    }
+
+    pub fn lambda(&self, w: Vec3) -> Float {
+        let cos2 = w.z * w.z;
+        if cos2 == 0. {
+            return Float::INFINITY;
+        }
+        let alpha2_tan2 = (self.alpha_x * self.alpha_x * w.x * w.x
+            + self.alpha_y * self.alpha_y * w.y * w.y)
+            / cos2;
+        ((1. + alpha2_tan2).sqrt() - 1.) / 2.
+    }
+
+    /// Smith masking of a single direction.
+    pub fn g1(&self, w: Vec3) -> Float {
+        1. / (1. + self.lambda(w))
+    }
+
+    /// Height-correlated Smith masking-shadowing.
+    pub fn g(&self, wo: Vec3, wi: Vec3) -> Float {
+        1. / (1. + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// Distribution of normals visible from `w`.
+    pub fn d_visible(&self, w: Vec3, wm: Vec3) -> Float {
+        if w.z == 0. {
+            return 0.;
+        }
+        self.g1(w) / w.z.abs() * self.d(wm) * w.dot(wm).abs()
+    }
+
+    /// Samples a microfacet normal from the visible normal distribution (Heitz 2018).
+    pub fn sample_wm(&self, w: Vec3, u: Vec2) -> Vec3 {
+        let w = if w.z < 0. { -w } else { w };
+
+        // stretch to the hemisphere configuration
+        let wh = Vec3::new(self.alpha_x * w.x, self.alpha_y * w.y, w.z).normalize();
+        let len2 = wh.x * wh.x + wh.y * wh.y;
+        let t1 = if len2 > 0. {
+            Vec3::new(-wh.y, wh.x, 0.) / len2.sqrt()
+        } else {
+            Vec3::X
+        };
+        let t2 = wh.cross(t1);
+
+        // sample the projected area of the visible hemisphere
+        let r = u.x.sqrt();
+        let phi = 2. * PI * u.y;
+        let p1 = r * phi.cos();
+        let mut p2 = r * phi.sin();
+        let s = 0.5 * (1. + wh.z);
+        p2 = (1. - s) * (1. - p1 * p1).max(0.).sqrt() + s * p2;
+
+        let nh = t1 * p1 + t2 * p2 + wh * (1. - p1 * p1 - p2 * p2).max(0.).sqrt();
+
+        // unstretch
+        Vec3::new(self.alpha_x * nh.x, self.alpha_y * nh.y, nh.z.max(1e-6)).normalize()
+    }
+}
+
+/// Schlick's `(1 - cos)^5` Fresnel weight.
+pub fn schlick_weight(cos_theta: Float) -> Float {
+    (1. - cos_theta).clamp(0., 1.).powi(5)
+}
+
+/// Unpolarized Fresnel reflectance of a dielectric interface with relative IOR `eta`.
+pub fn fresnel_dielectric(cos_i: Float, eta: Float) -> Float {
+    let mut cos_i = cos_i.clamp(-1., 1.);
+    let mut eta = eta;
+    if cos_i < 0. {
+        eta = 1. / eta;
+        cos_i = -cos_i;
+    }
+
+    let sin2_i = 1. - cos_i * cos_i;
+    let sin2_t = sin2_i / (eta * eta);
+    if sin2_t >= 1. {
+        return 1.;
+    }
+    let cos_t = (1. - sin2_t).max(0.).sqrt();
+
+    let r_parl = (eta * cos_i - cos_t) / (eta * cos_i + cos_t);
+    let r_perp = (cos_i - eta * cos_t) / (cos_i + eta * cos_t);
+    (r_parl * r_parl + r_perp * r_perp) / 2.
+}
+
+/// Minimal complex number, only used for conductor Fresnel terms.
+#[derive(Debug, Clone, Copy)]
+struct Complex {
+    re: Float,
+    im: Float,
+}
+
+impl Complex {
+    fn new(re: Float, im: Float) -> Self {
+        Self { re, im }
+    }
+
+    fn add(self, o: Complex) -> Complex {
+        Complex::new(self.re + o.re, self.im + o.im)
+    }
+
+    fn sub(self, o: Complex) -> Complex {
+        Complex::new(self.re - o.re, self.im - o.im)
+    }
+
+    fn mul(self, o: Complex) -> Complex {
+        Complex::new(
+            self.re * o.re - self.im * o.im,
+            self.re * o.im + self.im * o.re,
+        )
+    }
+
+    fn div(self, o: Complex) -> Complex {
+        let scale = 1. / (o.re * o.re + o.im * o.im);
+        Complex::new(
+            scale * (self.re * o.re + self.im * o.im),
+            scale * (self.im * o.re - self.re * o.im),
+        )
+    }
+
+    fn norm(self) -> Float {
+        self.re * self.re + self.im * self.im
+    }
+
+    fn sqrt(self) -> Complex {
+        let n = self.norm().sqrt();
+        if n == 0. {
+            return Complex::new(0., 0.);
+        }
+        let t1 = (0.5 * (n + self.re.abs())).sqrt();
+        let t2 = 0.5 * self.im / t1;
+        if self.re >= 0. {
+            Complex::new(t1, t2)
+        } else {
+            Complex::new(t2.abs(), t1.copysign(self.im))
+        }
+    }
+}
+
+/// Fresnel reflectance of a conductor with complex IOR `eta + i k`, for one wavelength.
+pub fn fresnel_complex(cos_i: Float, eta: Float, k: Float) -> Float {
+    let cos_i = cos_i.clamp(0., 1.);
+    let eta = Complex::new(eta, k);
+    let cos = Complex::new(cos_i, 0.);
+
+    let sin2_i = Complex::new(1. - cos_i * cos_i, 0.);
+    let sin2_t = sin2_i.div(eta.mul(eta));
+    let cos_t = Complex::new(1., 0.).sub(sin2_t).sqrt();
+
+    let r_parl = eta.mul(cos).sub(cos_t).div(eta.mul(cos).add(cos_t));
+    let r_perp = cos.sub(eta.mul(cos_t)).div(cos.add(eta.mul(cos_t)));
+    (r_parl.norm() + r_perp.norm()) / 2.
+}
+
+/// Per channel conductor Fresnel reflectance.
+pub fn fresnel_conductor(cos_i: Float, eta: Rgb, k: Rgb) -> Rgb {
+    Rgb::new(
+        fresnel_complex(cos_i, eta.r, k.r),
+        fresnel_complex(cos_i, eta.g, k.g),
+        fresnel_complex(cos_i, eta.b, k.b),
+    )
+}
+
+pub fn reflect(wo: Vec3, n: Vec3) -> Vec3 {
+    -wo + 2. * wo.dot(n) * n
+}
+
+/// Refracts `wi` through a surface with normal `n` and relative IOR `eta`,
+/// or `None` on total internal reflection.
+pub fn refract(wi: Vec3, n: Vec3, eta: Float) -> Option<Vec3> {
+    let mut cos_i = n.dot(wi);
+    let mut eta = eta;
+    let mut n = n;
+    if cos_i < 0. {
+        eta = 1. / eta;
+        cos_i = -cos_i;
+        n = -n;
+    }
+
+    let sin2_i = (1. - cos_i * cos_i).max(0.);
+    let sin2_t = sin2_i / (eta * eta);
+    if sin2_t >= 1. {
+        return None;
+    }
+    let cos_t = (1. - sin2_t).sqrt();
+
+    Some(-wi / eta + (cos_i / eta - cos_t) * n)
+}
+
+/// Rough (or smooth) dielectric interface. `eta` is the IOR on the far side of
+/// the surface divided by the IOR on the side of `wo`, and `wo.z` is assumed positive.
+#[derive(Debug, Clone, Copy)]
+pub struct DielectricLobe {
+    pub distribution: TrowbridgeReitz,
+    pub eta: Float,
+    /// color filter applied to transmitted light
+    pub tint: Rgb,
+}
+
+impl DielectricLobe {
+    pub fn eval(&self, wo: Vec3, wi: Vec3) -> Rgb {
+        if self.distribution.is_smooth() || self.eta == 1. || wo.z == 0. || wi.z == 0. {
+            return Rgb::black();
+        }
+
+        let reflect = wi.z > 0.;
+        let etap = if reflect { 1. } else { self.eta };
+        let Some(wm) = half_vector(wo, wi, etap) else {
+            return Rgb::black();
+        };
+
+        let f = fresnel_dielectric(wo.dot(wm), self.eta);
+        let d = self.distribution.d(wm);
+        let g = self.distribution.g(wo, wi);
+        if reflect {
+            let v = d * g * f / (4. * wi.z * wo.z).abs();
+            Rgb::splat(v)
+        } else {
+            let denom = (wi.dot(wm) + wo.dot(wm) / etap).powi(2) * wi.z * wo.z;
+            let v = d * (1. - f) * g * (wi.dot(wm) * wo.dot(wm) / denom).abs() / (etap * etap);
+            self.tint * v
+        }
+    }
+
+    pub fn pdf(&self, wo: Vec3, wi: Vec3) -> Float {
+        if self.distribution.is_smooth() || self.eta == 1. || wo.z == 0. || wi.z == 0. {
+            return 0.;
+        }
+
+        let reflect = wi.z > 0.;
+        let etap = if reflect { 1. } else { self.eta };
+        let Some(wm) = half_vector(wo, wi, etap) else {
+            return 0.;
+        };
+
+        let r = fresnel_dielectric(wo.dot(wm), self.eta);
+        let d_visible = self.distribution.d_visible(wo, wm);
+        if reflect {
+            d_visible / (4. * wo.dot(wm).abs()) * r
+        } else {
+            let denom = (wi.dot(wm) + wo.dot(wm) / etap).powi(2);
+            d_visible * wi.dot(wm).abs() / denom * (1. - r)
+        }
+    }
+
+    /// `u_lobe` picks reflection or transmission, `u` samples the microfacet normal.
+    pub fn sample(&self, wo: Vec3, u_lobe: Float, u: Vec2) -> Option<BsdfSample> {
+        if self.distribution.is_smooth() || self.eta == 1. {
+            return self.sample_smooth(wo, u_lobe);
+        }
+
+        let wm = self.distribution.sample_wm(wo, u);
+        let r = fresnel_dielectric(wo.dot(wm), self.eta);
+
+        let wi = if u_lobe < r {
+            reflect(wo, wm)
+        } else {
+            let wi = refract(wo, wm, self.eta)?;
+            if wi.z >= 0. {
+                return None;
+            }
+            wi
+        };
+        if wi.z == 0. || (wi.z > 0.) != (u_lobe < r) {
+            return None;
+        }
+
+        let pdf = self.pdf(wo, wi);
+        if pdf <= 0. {
+            return None;
+        }
+        Some(BsdfSample {
+            wi,
+            f: self.eval(wo, wi),
+            pdf,
+            delta: false,
+            subsurface: None,
+        })
+    }
+
+    fn sample_smooth(&self, wo: Vec3, u_lobe: Float) -> Option<BsdfSample> {
+        let r = fresnel_dielectric(wo.z, self.eta);
+        if u_lobe < r {
+            let wi = Vec3::new(-wo.x, -wo.y, wo.z);
+            let f = r / wi.z.abs();
+            Some(BsdfSample {
+                wi,
+                f: Rgb::splat(f),
+                pdf: r,
+                delta: true,
+                subsurface: None,
+            })
+        } else {
+            let wi = refract(wo, Vec3::Z, self.eta)?;
+            let t = 1. - r;
+            let f = self.tint * (t / wi.z.abs() / (self.eta * self.eta));
+            Some(BsdfSample {
+                wi,
+                f,
+                pdf: t,
+                delta: true,
+                subsurface: None,
+            })
+        }
+    }
+}
+
+/// Rough (or smooth) metal, reflecting according to its complex index of refraction.
+#[derive(Debug, Clone, Copy)]
+pub struct ConductorLobe {
+    pub distribution: TrowbridgeReitz,
+    pub eta: Rgb,
+    pub k: Rgb,
+}
+
+impl ConductorLobe {
+    pub fn eval(&self, wo: Vec3, wi: Vec3) -> Rgb {
+        if self.distribution.is_smooth() || wo.z <= 0. || wi.z <= 0. {
+            return Rgb::black();
+        }
+        let wm = wo + wi;
+        if wm.length_squared() == 0. {
+            return Rgb::black();
+        }
+        let wm = wm.normalize();
+
+        let f = fresnel_conductor(wo.dot(wm).abs(), self.eta, self.k);
+        let s = self.distribution.d(wm) * self.distribution.g(wo, wi) / (4. * wi.z * wo.z);
+        f * s
+    }
+
+    pub fn pdf(&self, wo: Vec3, wi: Vec3) -> Float {
+        if self.distribution.is_smooth() || wo.z <= 0. || wi.z <= 0. {
+            return 0.;
+        }
+        let wm = wo + wi;
+        if wm.length_squared() == 0. {
+            return 0.;
+        }
+        let wm = wm.normalize();
+
+        self.distribution.d_visible(wo, wm) / (4. * wo.dot(wm).abs())
+    }
+
+    pub fn sample(&self, wo: Vec3, u: Vec2) -> Option<BsdfSample> {
+        if wo.z <= 0. {
+            return None;
+        }
+
+        if self.distribution.is_smooth() {
+            let wi = Vec3::new(-wo.x, -wo.y, wo.z);
+            let f = fresnel_conductor(wi.z, self.eta, self.k);
+            return Some(BsdfSample {
+                wi,
+                f: f / wi.z,
+                pdf: 1.,
+                delta: true,
+                subsurface: None,
+            });
+        }
+
+        let wm = self.distribution.sample_wm(wo, u);
+        let wi = reflect(wo, wm);
+        if wi.z <= 0. {
+            return None;
+        }
+
+        let pdf = self.pdf(wo, wi);
+        if pdf <= 0. {
+            return None;
+        }
+        Some(BsdfSample {
+            wi,
+            f: self.eval(wo, wi),
+            pdf,
+            delta: false,
+            subsurface: None,
+        })
+    }
+}
+
+/// Generalized half vector for reflection (`etap == 1`) and refraction,
+/// oriented towards +z. `None` for configurations no microfacet can produce.
+fn half_vector(wo: Vec3, wi: Vec3, etap: Float) -> Option<Vec3> {
+    let wm = wi * etap + wo;
+    if wm.length_squared() == 0. {
+        return None;
+    }
+    let wm = wm.normalize();
+    let wm = if wm.z < 0. { -wm } else { wm };
+
+    // discard back facing microfacets
+    if wm.dot(wi) * wi.z < 0. || wm.dot(wo) * wo.z < 0. {
+        return None;
+    }
+    Some(wm)
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::{Float, Vec2, Vec3};
+
+    use crate::raytracer::microfacet::{
+        fresnel_complex, fresnel_dielectric, refract, TrowbridgeReitz,
+    };
+
+    #[test]
+    fn test_fresnel_dielectric() {
+        // ~4% reflectance at normal incidence for glass
+        assert!((fresnel_dielectric(1., 1.5) - 0.04).abs() < 1e-3);
+        // total internal reflection leaving glass at a grazing angle
+        assert_eq!(fresnel_dielectric(0.1, 1. / 1.5), 1.);
+    }
+
+    #[test]
+    fn test_fresnel_complex() {
+        // with no absorption the conductor term reduces to the dielectric one
+        for cos_i in [0.1, 0.5, 1.] {
+            let a = fresnel_complex(cos_i, 1.5, 0.);
+            let b = fresnel_dielectric(cos_i, 1.5);
+            assert!((a - b).abs() < 1e-5);
+        }
+        // silver-like metals are highly reflective at normal incidence
+        assert!(fresnel_complex(1., 0.155, 4.828) > 0.95);
+    }
+
+    #[test]
+    fn test_refract_straight_through() {
+        let wt = refract(Vec3::Z, Vec3::Z, 1.5).unwrap();
+        assert!((wt - -Vec3::Z).length() < 1e-6);
+    }
+
+    #[test]
+    fn test_visible_normals_face_viewer() {
+        let distribution = TrowbridgeReitz::new(0.5, 0.2);
+        let wo = Vec3::new(0.5, 0.3, 0.8).normalize();
+
+        for i in 0..16 {
+            for j in 0..16 {
+                let u = Vec2::new((i as Float + 0.5) / 16., (j as Float + 0.5) / 16.);
+                let wm = distribution.sample_wm(wo, u);
+                assert!(wm.z > 0.);
+                assert!(wm.dot(wo) >= -1e-5);
+            }
+        }
+    }
+}
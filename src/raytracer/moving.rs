@@ -0,0 +1,107 @@
+use super::{
+    aabb::Aabb,
+    geometry::{gamma, Direction},
+    hit_info::{HitInfo, Hittable},
+    math::{Float, Vec3},
+    ray::Ray,
+};
+
+/// Translates `object` linearly from `offset0` at `time0` to `offset1` at `time1`,
+/// so rays traced at different times during the shutter interval see it move.
+pub struct Moving {
+    pub object: Box<dyn Hittable>,
+    pub offset0: Vec3,
+    pub offset1: Vec3,
+    pub time0: Float,
+    pub time1: Float,
+}
+
+impl Moving {
+    pub fn new(
+        object: Box<dyn Hittable>,
+        offset0: Vec3,
+        offset1: Vec3,
+        time0: Float,
+        time1: Float,
+    ) -> Self {
+        Self {
+            object,
+            offset0,
+            offset1,
+            time0,
+            time1,
+        }
+    }
+
+    /// Moves `object` from where it is by `displacement` over the `[0, 1]` shutter interval.
+    pub fn linear(object: Box<dyn Hittable>, displacement: Vec3) -> Self {
+        Self::new(object, Vec3::ZERO, displacement, 0., 1.)
+    }
+
+    pub fn offset(&self, time: Float) -> Vec3 {
+        let span = self.time1 - self.time0;
+        let s = if span > 0. {
+            ((time - self.time0) / span).clamp(0., 1.)
+        } else {
+            0.
+        };
+        self.offset0.lerp(self.offset1, s)
+    }
+}
+
+impl Hittable for Moving {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitInfo> {
+        let offset = Direction::from_vec3(self.offset(ray.time));
+        let moved = Ray::with_time(ray.origin - offset, ray.direction, ray.time);
+
+        let mut hit_info = self.object.hit(&moved, t_min, t_max)?;
+        hit_info.p += offset;
+        hit_info.p_error += gamma(1) * hit_info.p.abs();
+
+        Some(hit_info)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // cover the whole motion, so acceleration structures don't miss any time sample
+        let bounds = self.object.bounding_box()?;
+        let start = Aabb::new(bounds.min + self.offset0, bounds.max + self.offset0);
+        let end = Aabb::new(bounds.min + self.offset1, bounds.max + self.offset1);
+        Some(Aabb::surrounding(start, end))
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::{Float, Vec3};
+
+    use crate::raytracer::{
+        geometry::{Direction, Point},
+        hit_info::Hittable,
+        material::MaterialKind,
+        moving::Moving,
+        radiance::Rgb,
+        ray::Ray,
+        sphere::Sphere,
+    };
+
+    #[test]
+    fn test_moving_sphere() {
+        let material = MaterialKind::Lambertian {
+            albedo: Rgb::white(),
+        };
+        let sphere = Sphere::new(Point::ZERO, 0.5, material);
+        let moving = Moving::linear(Box::new(sphere), Vec3::new(2., 0., 0.));
+
+        let ray = Ray::with_time(Point::new(2., 0., -5.), Direction::Z, 0.);
+        assert!(moving.hit(&ray, 0.001, Float::INFINITY).is_none());
+
+        let ray = Ray::with_time(Point::new(2., 0., -5.), Direction::Z, 1.);
+        let hit = moving.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!((*hit.p - Vec3::new(2., 0., -0.5)).length() < 1e-5);
+
+        let bounds = moving.bounding_box().unwrap();
+        assert_eq!(bounds.min.x, -0.5);
+        assert_eq!(bounds.max.x, 2.5);
+    }
+}
@@ -0,0 +1,103 @@
+use super::{
+    aabb::Aabb,
+    geometry::{orthonormal_basis, reproject_onto_plane, Direction, Normal, Point},
+    hit_info::{HitInfo, Hittable},
+    material::MaterialKind,
+    math::{Float, Vec3},
+    ray::Ray,
+};
+
+/// Infinite plane through `point`, facing along `normal`.
+pub struct Plane {
+    pub point: Point,
+    pub normal: Vec3,
+    pub material: MaterialKind,
+    tangent: Vec3,
+    bitangent: Vec3,
+}
+
+impl Plane {
+    pub fn new(point: Point, normal: Vec3, material: MaterialKind) -> Self {
+        let normal = normal.normalize();
+        let (tangent, bitangent) = orthonormal_basis(normal);
+
+        Self {
+            point,
+            normal,
+            material,
+            tangent,
+            bitangent,
+        }
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitInfo> {
+        let denom = self.normal.dot(*ray.direction);
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = self.normal.dot(*(self.point - ray.origin)) / denom;
+        if t <= t_min || t >= t_max {
+            return None;
+        }
+
+        let (p, p_error) = reproject_onto_plane(ray.at(t), self.point, self.normal);
+        let offset = *(p - self.point);
+
+        let mut hit_info = HitInfo::new(
+            true,
+            t,
+            p,
+            p_error,
+            Normal::from_vec3(self.normal),
+            self.material.clone(),
+        );
+        hit_info.set_face_normal(ray.direction, Normal::from_vec3(self.normal));
+        // planar mapping in world units, so a checker scale is a size in the scene
+        hit_info.set_uv(offset.dot(self.tangent), offset.dot(self.bitangent));
+        hit_info.set_tangents(
+            Direction::from_vec3(self.tangent),
+            Direction::from_vec3(self.bitangent),
+        );
+
+        Some(hit_info)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::{Float, Vec3};
+
+    use crate::raytracer::{
+        geometry::{Direction, Point},
+        hit_info::Hittable,
+        material::MaterialKind,
+        plane::Plane,
+        radiance::Rgb,
+        ray::Ray,
+    };
+
+    #[test]
+    fn test_plane_hit() {
+        let material = MaterialKind::Lambertian {
+            albedo: Rgb::white(),
+        };
+        let plane = Plane::new(Point::new(0., 1., 0.), Vec3::new(0., 1., 0.), material);
+
+        let ray = Ray::new(Point::new(100., 5., -30.), Direction::new(0., -1., 0.));
+        let hit = plane.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!((hit.t - 4.).abs() < 1e-5);
+        assert!(hit.front_face);
+
+        let ray = Ray::new(Point::new(0., 5., 0.), Direction::new(1., 0., 0.));
+        assert!(plane.hit(&ray, 0.001, Float::INFINITY).is_none());
+        assert!(plane.bounding_box().is_none());
+    }
+}
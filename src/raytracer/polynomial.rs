@@ -0,0 +1,221 @@
+//! Closed-form polynomial root finders, after Jochen Schwarze's
+//! "Cubic and Quartic Roots" (Graphics Gems I).
+//!
+//! Coefficients are given lowest degree first: `c[0] + c[1] x + c[2] x^2 + ...`.
+//! Computation happens in f64 since the quartic is badly conditioned in f32.
+
+use std::f64::consts::PI;
+
+use glam::DVec3;
+
+use super::math::{Float, Vec3};
+
+// with the f64 feature `Float` already is f64, so these conversions are no-ops
+
+/// `x` in the f64 the solvers work in.
+#[allow(clippy::unnecessary_cast)]
+pub fn widen(x: Float) -> f64 {
+    x as f64
+}
+
+/// A solver result back in `Float`.
+#[allow(clippy::unnecessary_cast)]
+pub fn narrow(x: f64) -> Float {
+    x as Float
+}
+
+pub fn widen_vec3(v: Vec3) -> DVec3 {
+    DVec3::new(widen(v.x), widen(v.y), widen(v.z))
+}
+
+pub fn narrow_vec3(v: DVec3) -> Vec3 {
+    Vec3::new(narrow(v.x), narrow(v.y), narrow(v.z))
+}
+
+const EPSILON: f64 = 1e-9;
+
+fn is_zero(x: f64) -> bool {
+    x.abs() < EPSILON
+}
+
+/// Real roots of `c[0] + c[1] x + c[2] x^2`.
+pub fn solve_quadratic(c: [f64; 3]) -> Vec<f64> {
+    if is_zero(c[2]) {
+        if is_zero(c[1]) {
+            return vec![];
+        }
+        return vec![-c[0] / c[1]];
+    }
+
+    // normal form: x^2 + px + q = 0
+    let p = c[1] / (2. * c[2]);
+    let q = c[0] / c[2];
+    let d = p * p - q;
+
+    if is_zero(d) {
+        vec![-p]
+    } else if d < 0. {
+        vec![]
+    } else {
+        let sqrt_d = d.sqrt();
+        vec![sqrt_d - p, -sqrt_d - p]
+    }
+}
+
+/// Real roots of `c[0] + c[1] x + c[2] x^2 + c[3] x^3`.
+pub fn solve_cubic(c: [f64; 4]) -> Vec<f64> {
+    if is_zero(c[3]) {
+        return solve_quadratic([c[0], c[1], c[2]]);
+    }
+
+    // normal form: x^3 + Ax^2 + Bx + C = 0
+    let a = c[2] / c[3];
+    let b = c[1] / c[3];
+    let cc = c[0] / c[3];
+
+    // substitute x = y - A/3 to eliminate the quadric term: y^3 + 3py + 2q = 0
+    let sq_a = a * a;
+    let p = (-sq_a / 3. + b) / 3.;
+    let q = (2. / 27. * a * sq_a - a * b / 3. + cc) / 2.;
+
+    let cb_p = p * p * p;
+    let d = q * q + cb_p;
+
+    let mut roots = if is_zero(d) {
+        if is_zero(q) {
+            vec![0.]
+        } else {
+            let u = (-q).cbrt();
+            vec![2. * u, -u]
+        }
+    } else if d < 0. {
+        // three real solutions
+        let phi = (-q / (-cb_p).sqrt()).clamp(-1., 1.).acos() / 3.;
+        let t = 2. * (-p).sqrt();
+        vec![
+            t * phi.cos(),
+            -t * (phi + PI / 3.).cos(),
+            -t * (phi - PI / 3.).cos(),
+        ]
+    } else {
+        let sqrt_d = d.sqrt();
+        let u = (sqrt_d - q).cbrt();
+        let v = -(sqrt_d + q).cbrt();
+        vec![u + v]
+    };
+
+    let sub = a / 3.;
+    for r in &mut roots {
+        *r -= sub;
+    }
+    roots
+}
+
+/// Real roots of `c[0] + c[1] x + c[2] x^2 + c[3] x^3 + c[4] x^4`, in ascending order.
+pub fn solve_quartic(c: [f64; 5]) -> Vec<f64> {
+    if is_zero(c[4]) {
+        let mut roots = solve_cubic([c[0], c[1], c[2], c[3]]);
+        roots.sort_by(|a, b| a.total_cmp(b));
+        return roots;
+    }
+
+    // normal form: x^4 + Ax^3 + Bx^2 + Cx + D = 0
+    let a = c[3] / c[4];
+    let b = c[2] / c[4];
+    let cc = c[1] / c[4];
+    let d = c[0] / c[4];
+
+    // substitute x = y - A/4 to eliminate the cubic term: y^4 + py^2 + qy + r = 0
+    let sq_a = a * a;
+    let p = -3. / 8. * sq_a + b;
+    let q = sq_a * a / 8. - a * b / 2. + cc;
+    let r = -3. / 256. * sq_a * sq_a + sq_a * b / 16. - a * cc / 4. + d;
+
+    let mut roots = if is_zero(r) {
+        // no absolute term: y(y^3 + py + q) = 0
+        let mut roots = solve_cubic([q, p, 0., 1.]);
+        roots.push(0.);
+        roots
+    } else {
+        // solve the resolvent cubic and take one real root
+        let z = solve_cubic([r * p / 2. - q * q / 8., -r, -p / 2., 1.])[0];
+
+        let u = z * z - r;
+        let v = 2. * z - p;
+        let u = if is_zero(u) {
+            0.
+        } else if u > 0. {
+            u.sqrt()
+        } else {
+            return vec![];
+        };
+        let v = if is_zero(v) {
+            0.
+        } else if v > 0. {
+            v.sqrt()
+        } else {
+            return vec![];
+        };
+
+        let mut roots = solve_quadratic([z - u, if q < 0. { -v } else { v }, 1.]);
+        roots.extend(solve_quadratic([z + u, if q < 0. { v } else { -v }, 1.]));
+        roots
+    };
+
+    let sub = a / 4.;
+    for root in &mut roots {
+        *root = polish(c, *root - sub);
+    }
+    roots.sort_by(|a, b| a.total_cmp(b));
+    roots
+}
+
+/// A couple of Newton steps to recover precision lost in the closed form.
+fn polish(c: [f64; 5], mut x: f64) -> f64 {
+    for _ in 0..2 {
+        let f = (((c[4] * x + c[3]) * x + c[2]) * x + c[1]) * x + c[0];
+        let df = ((4. * c[4] * x + 3. * c[3]) * x + 2. * c[2]) * x + c[1];
+        if df.abs() < EPSILON {
+            break;
+        }
+        x -= f / df;
+    }
+    x
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::polynomial::{solve_cubic, solve_quadratic, solve_quartic};
+
+    fn assert_roots(mut roots: Vec<f64>, expected: &[f64]) {
+        roots.sort_by(|a, b| a.total_cmp(b));
+        assert_eq!(roots.len(), expected.len(), "{:?}", roots);
+        for (r, e) in roots.iter().zip(expected) {
+            assert!((r - e).abs() < 1e-6, "{:?} != {:?}", roots, expected);
+        }
+    }
+
+    #[test]
+    fn test_quadratic() {
+        // (x - 1)(x - 3)
+        assert_roots(solve_quadratic([3., -4., 1.]), &[1., 3.]);
+        assert_roots(solve_quadratic([1., 0., 1.]), &[]);
+    }
+
+    #[test]
+    fn test_cubic() {
+        // (x - 1)(x - 2)(x - 3)
+        assert_roots(solve_cubic([-6., 11., -6., 1.]), &[1., 2., 3.]);
+    }
+
+    #[test]
+    fn test_quartic() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        assert_roots(solve_quartic([24., -50., 35., -10., 1.]), &[1., 2., 3., 4.]);
+        // (x^2 + 1)(x - 2)(x + 2)
+        assert_roots(solve_quartic([-4., 0., -3., 0., 1.]), &[-2., 2.]);
+        // x^4 + 1 has no real roots
+        assert_roots(solve_quartic([1., 0., 0., 0., 1.]), &[]);
+    }
+}
@@ -0,0 +1,389 @@
+//! Disney-style principled BSDF: a single material whose parameters blend
+//! diffuse, sheen, specular, clearcoat and transmission lobes.
+
+use quad_rand::RandomRange;
+
+use super::{
+    bsdf::{BsdfSample, ShadingFrame},
+    math::{consts::PI, Float, Vec2, Vec3},
+    microfacet::{reflect, schlick_weight, DielectricLobe, TrowbridgeReitz, SMOOTH_ALPHA},
+    radiance::Rgb,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Principled {
+    pub base_color: Rgb,
+    pub metallic: Float,
+    pub roughness: Float,
+    /// dielectric specular amount, 0.5 matches an IOR of 1.5
+    pub specular: Float,
+    pub specular_tint: Float,
+    pub sheen: Float,
+    pub sheen_tint: Float,
+    pub clearcoat: Float,
+    pub clearcoat_gloss: Float,
+    pub transmission: Float,
+    pub ior: Float,
+}
+
+impl Default for Principled {
+    fn default() -> Self {
+        Self {
+            base_color: Rgb::new(0.8, 0.8, 0.8),
+            metallic: 0.,
+            roughness: 0.5,
+            specular: 0.5,
+            specular_tint: 0.,
+            sheen: 0.,
+            sheen_tint: 0.5,
+            clearcoat: 0.,
+            clearcoat_gloss: 1.,
+            transmission: 0.,
+            ior: 1.5,
+        }
+    }
+}
+
+/// Probabilities of sampling each lobe.
+struct LobeWeights {
+    diffuse: Float,
+    specular: Float,
+    clearcoat: Float,
+    transmission: Float,
+}
+
+impl Principled {
+    pub fn new(base_color: Rgb, metallic: Float, roughness: Float) -> Self {
+        Self {
+            base_color,
+            metallic,
+            roughness,
+            ..Default::default()
+        }
+    }
+
+    fn tint(&self) -> Rgb {
+        let l = self.base_color.luminance();
+        if l > 0. {
+            self.base_color / l
+        } else {
+            Rgb::white()
+        }
+    }
+
+    /// Specular color at normal incidence.
+    fn spec0(&self) -> Rgb {
+        let dielectric =
+            Rgb::white().lerp(self.tint(), self.specular_tint) * (self.specular * 0.08);
+        dielectric.lerp(self.base_color, self.metallic)
+    }
+
+    fn distribution(&self) -> TrowbridgeReitz {
+        TrowbridgeReitz::from_roughness(self.roughness)
+    }
+
+    fn clearcoat_alpha(&self) -> Float {
+        0.1 + (0.001 - 0.1) * self.clearcoat_gloss
+    }
+
+    /// Fraction of the diffuse and sheen terms.
+    fn diffuse_amount(&self) -> Float {
+        (1. - self.metallic) * (1. - self.transmission)
+    }
+
+    fn transmission_amount(&self) -> Float {
+        (1. - self.metallic) * self.transmission
+    }
+
+    fn transmission_lobe(&self, front_face: bool) -> DielectricLobe {
+        DielectricLobe {
+            distribution: self.distribution(),
+            eta: if front_face { self.ior } else { 1. / self.ior },
+            tint: self.base_color.map(Float::sqrt),
+        }
+    }
+
+    fn lobe_weights(&self, wo: Vec3) -> LobeWeights {
+        let f = self.spec0().lerp(Rgb::white(), schlick_weight(wo.z));
+        let mut w = LobeWeights {
+            diffuse: self.diffuse_amount() * self.base_color.luminance(),
+            specular: f.luminance() * (1. - self.transmission_amount()),
+            clearcoat: 0.25 * self.clearcoat,
+            transmission: self.transmission_amount(),
+        };
+
+        let total = w.diffuse + w.specular + w.clearcoat + w.transmission;
+        if total <= 0. {
+            w.diffuse = 1.;
+            return w;
+        }
+        w.diffuse /= total;
+        w.specular /= total;
+        w.clearcoat /= total;
+        w.transmission /= total;
+        w
+    }
+
+    /// BSDF value for local directions `wo` (towards the viewer, `wo.z > 0`) and `wi`.
+    /// Perfectly smooth lobes are not included since they are zero almost everywhere.
+    pub fn eval_local(&self, wo: Vec3, wi: Vec3, front_face: bool) -> Rgb {
+        let mut f = Rgb::black();
+
+        if self.transmission_amount() > 0. {
+            let t = self.transmission_lobe(front_face).eval(wo, wi);
+            f += t * self.transmission_amount();
+        }
+
+        if wi.z <= 0. || wo.z <= 0. {
+            return f;
+        }
+
+        let wh = (wo + wi).normalize();
+        let cos_d = wi.dot(wh);
+        let fl = schlick_weight(wi.z);
+        let fv = schlick_weight(wo.z);
+        let fd = schlick_weight(cos_d);
+
+        // diffuse with retro-reflection at grazing angles, plus sheen
+        let fd90 = 0.5 + 2. * self.roughness * cos_d * cos_d;
+        let diffuse = (1. + (fd90 - 1.) * fl) * (1. + (fd90 - 1.) * fv) / PI;
+        let sheen = Rgb::white().lerp(self.tint(), self.sheen_tint) * (self.sheen * fd);
+        let base = self.base_color * diffuse + sheen;
+        f += base * self.diffuse_amount();
+
+        // specular reflection
+        let distribution = self.distribution();
+        if distribution.alpha_x >= SMOOTH_ALPHA {
+            let fresnel = self.spec0().lerp(Rgb::white(), fd);
+            let s = distribution.d(wh) * distribution.g(wo, wi) / (4. * wi.z * wo.z);
+            f += fresnel * (s * (1. - self.transmission_amount()));
+        }
+
+        // clearcoat: fixed IOR 1.5 layer with a GTR1 distribution
+        if self.clearcoat > 0. {
+            let fr = 0.04 + 0.96 * fd;
+            let coat = TrowbridgeReitz::new(0.25, 0.25);
+            let c =
+                0.25 * self.clearcoat * gtr1(wh.z, self.clearcoat_alpha()) * fr * coat.g(wo, wi)
+                    / (4. * wi.z * wo.z);
+            f += Rgb::splat(c);
+        }
+
+        f
+    }
+
+    pub fn pdf_local(&self, wo: Vec3, wi: Vec3, front_face: bool) -> Float {
+        let w = self.lobe_weights(wo);
+        let mut pdf = 0.;
+
+        if w.transmission > 0. {
+            pdf += w.transmission * self.transmission_lobe(front_face).pdf(wo, wi);
+        }
+
+        if wi.z <= 0. || wo.z <= 0. {
+            return pdf;
+        }
+
+        let wh = (wo + wi).normalize();
+        pdf += w.diffuse * wi.z / PI;
+
+        let distribution = self.distribution();
+        if distribution.alpha_x >= SMOOTH_ALPHA {
+            pdf += w.specular * distribution.d_visible(wo, wh) / (4. * wo.dot(wh));
+        }
+
+        if w.clearcoat > 0. {
+            pdf += w.clearcoat * gtr1(wh.z, self.clearcoat_alpha()) * wh.z / (4. * wo.dot(wh));
+        }
+
+        pdf
+    }
+
+    pub fn sample_local(&self, wo: Vec3, front_face: bool) -> Option<BsdfSample> {
+        let w = self.lobe_weights(wo);
+        let u_lobe = RandomRange::gen_range(0., 1.);
+        let u = Vec2::new(
+            RandomRange::gen_range(0., 1.),
+            RandomRange::gen_range(0., 1.),
+        );
+
+        let distribution = self.distribution();
+        let mut wi = if u_lobe < w.diffuse {
+            cosine_hemisphere(u)
+        } else if u_lobe < w.diffuse + w.specular {
+            if distribution.alpha_x < SMOOTH_ALPHA {
+                // mirror-like: pick the delta lobe explicitly
+                let wi = Vec3::new(-wo.x, -wo.y, wo.z);
+                let f = self.spec0().lerp(Rgb::white(), schlick_weight(wo.z));
+                let f = f * ((1. - self.transmission_amount()) / wi.z);
+                return Some(BsdfSample {
+                    wi,
+                    f,
+                    pdf: w.specular,
+                    delta: true,
+                    subsurface: None,
+                });
+            }
+            reflect(wo, distribution.sample_wm(wo, u))
+        } else if u_lobe < w.diffuse + w.specular + w.clearcoat {
+            let wh = sample_gtr1(self.clearcoat_alpha(), u);
+            reflect(wo, wh)
+        } else {
+            let lobe = self.transmission_lobe(front_face);
+            let u_inner = RandomRange::gen_range(0., 1.);
+            let sample = lobe.sample(wo, u_inner, u)?;
+            if sample.delta {
+                return Some(BsdfSample {
+                    f: sample.f * self.transmission_amount(),
+                    pdf: sample.pdf * w.transmission,
+                    ..sample
+                });
+            }
+            sample.wi
+        };
+
+        if wi.length_squared() == 0. {
+            return None;
+        }
+        wi = wi.normalize();
+
+        let pdf = self.pdf_local(wo, wi, front_face);
+        if pdf <= 0. {
+            return None;
+        }
+        Some(BsdfSample {
+            wi,
+            f: self.eval_local(wo, wi, front_face),
+            pdf,
+            delta: false,
+            subsurface: None,
+        })
+    }
+
+    /// World-space evaluation in `frame`, whose normal is the shading normal facing `wo`.
+    pub fn eval(&self, wo: Vec3, wi: Vec3, frame: ShadingFrame, front_face: bool) -> Rgb {
+        self.eval_local(frame.to_local(wo), frame.to_local(wi), front_face)
+    }
+
+    pub fn pdf(&self, wo: Vec3, wi: Vec3, frame: ShadingFrame, front_face: bool) -> Float {
+        self.pdf_local(frame.to_local(wo), frame.to_local(wi), front_face)
+    }
+
+    pub fn sample(&self, wo: Vec3, frame: ShadingFrame, front_face: bool) -> Option<BsdfSample> {
+        let sample = self.sample_local(frame.to_local(wo), front_face)?;
+        Some(BsdfSample {
+            wi: frame.to_world(sample.wi),
+            ..sample
+        })
+    }
+}
+
+/// Generalized Trowbridge-Reitz with gamma = 1, used by the clearcoat lobe.
+fn gtr1(cos_h: Float, alpha: Float) -> Float {
+    if alpha >= 1. {
+        return 1. / PI;
+    }
+    let a2 = alpha * alpha;
+    let t = 1. + (a2 - 1.) * cos_h * cos_h;
+    (a2 - 1.) / (PI * a2.ln() * t)
+}
+
+fn sample_gtr1(alpha: Float, u: Vec2) -> Vec3 {
+    let a2 = alpha * alpha;
+    let cos_theta = ((1. - a2.powf(1. - u.x)) / (1. - a2)).max(0.).sqrt();
+    let sin_theta = (1. - cos_theta * cos_theta).max(0.).sqrt();
+    let phi = 2. * PI * u.y;
+    Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta)
+}
+
+pub fn cosine_hemisphere(u: Vec2) -> Vec3 {
+    let r = u.x.sqrt();
+    let phi = 2. * PI * u.y;
+    Vec3::new(r * phi.cos(), r * phi.sin(), (1. - u.x).max(0.).sqrt())
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::{Float, Vec3};
+
+    use crate::raytracer::{principled::Principled, radiance::Rgb};
+
+    /// The sampled weight must equal eval * cos / pdf for non-delta lobes.
+    #[test]
+    fn test_sample_consistent_with_eval_and_pdf() {
+        let material = Principled {
+            base_color: Rgb::new(0.8, 0.4, 0.2),
+            metallic: 0.3,
+            roughness: 0.4,
+            clearcoat: 0.5,
+            sheen: 0.3,
+            transmission: 0.2,
+            ..Default::default()
+        };
+        let wo = Vec3::new(0.3, -0.2, 0.9).normalize();
+
+        for _ in 0..200 {
+            if let Some(s) = material.sample_local(wo, true) {
+                assert!(s.pdf > 0.);
+                if !s.delta {
+                    let f = material.eval_local(wo, s.wi, true);
+                    assert!((f.r - s.f.r).abs() < 1e-4);
+                    let pdf = material.pdf_local(wo, s.wi, true);
+                    assert!((pdf - s.pdf).abs() <= 1e-4 * pdf.max(1.));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_diffuse_energy_bounded() {
+        let material = Principled::new(Rgb::white(), 0., 1.);
+        let wo = Vec3::new(0., 0., 1.);
+
+        let n = 4000;
+        let mut total = 0.;
+        for _ in 0..n {
+            if let Some(s) = material.sample_local(wo, true) {
+                total += s.f.r * s.wi.z.abs() / s.pdf;
+            }
+        }
+        let albedo = total / n as Float;
+        assert!(albedo > 0.5 && albedo < 1.1, "albedo {}", albedo);
+    }
+
+    #[test]
+    fn test_smooth_lobes_keep_their_energy() {
+        let wo = Vec3::Z;
+        // at normal incidence both the specular lobe and the glass reflect 4%,
+        // and the glass transmits the rest, compressed into the denser medium
+        for (transmission, reflected, transmitted) in
+            [(0., 0.04, 0.), (0.5, 0.04, 0.5 * 0.96 / (1.5 * 1.5))]
+        {
+            let material = Principled {
+                base_color: Rgb::white(),
+                roughness: 0.,
+                transmission,
+                ..Default::default()
+            };
+            let n = 20000;
+            let (mut r, mut t) = (0., 0.);
+            for _ in 0..n {
+                let Some(s) = material.sample_local(wo, true) else {
+                    continue;
+                };
+                if s.delta {
+                    let weight = s.f.g * s.wi.z.abs() / s.pdf;
+                    if s.wi.z > 0. {
+                        r += weight;
+                    } else {
+                        t += weight;
+                    }
+                }
+            }
+            let (r, t) = (r / n as Float, t / n as Float);
+            assert!((r - reflected).abs() < 0.004, "reflected {r}");
+            assert!((t - transmitted).abs() < 0.01, "transmitted {t}");
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use super::{
+    aabb::Aabb,
+    geometry::{reproject_onto_plane, Direction, Normal, Point},
+    hit_info::{HitInfo, Hittable},
+    material::MaterialKind,
+    math::{Float, Vec3},
+    ray::Ray,
+};
+
+/// Parallelogram spanned by the edges `u` and `v` starting at corner `q`.
+pub struct Quad {
+    pub q: Point,
+    pub u: Vec3,
+    pub v: Vec3,
+    pub material: MaterialKind,
+    normal: Vec3,
+    d: Float,
+    w: Vec3,
+}
+
+impl Quad {
+    pub fn new(q: Point, u: Vec3, v: Vec3, material: MaterialKind) -> Self {
+        let n = u.cross(v);
+        let normal = n.normalize();
+        let d = normal.dot(*q);
+        let w = n / n.dot(n);
+
+        Self {
+            q,
+            u,
+            v,
+            material,
+            normal,
+            d,
+            w,
+        }
+    }
+
+    pub fn normal(&self) -> Vec3 {
+        self.normal
+    }
+
+    pub fn area(&self) -> Float {
+        self.u.cross(self.v).length()
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitInfo> {
+        let denom = self.normal.dot(*ray.direction);
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = (self.d - self.normal.dot(*ray.origin)) / denom;
+        if t <= t_min || t >= t_max {
+            return None;
+        }
+
+        // express the hit point in the (u, v) frame of the quad
+        let (p, p_error) = reproject_onto_plane(ray.at(t), self.q, self.normal);
+        let planar = *(p - self.q);
+        let alpha = self.w.dot(planar.cross(self.v));
+        let beta = self.w.dot(self.u.cross(planar));
+        if !(0. ..=1.).contains(&alpha) || !(0. ..=1.).contains(&beta) {
+            return None;
+        }
+
+        let mut hit_info = HitInfo::new(
+            true,
+            t,
+            p,
+            p_error,
+            Normal::from_vec3(self.normal),
+            self.material.clone(),
+        );
+        hit_info.set_face_normal(ray.direction, Normal::from_vec3(self.normal));
+        hit_info.set_uv(alpha, beta);
+        hit_info.set_tangents(Direction::from_vec3(self.u), Direction::from_vec3(self.v));
+
+        Some(hit_info)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let corners = [
+            *self.q,
+            *self.q + self.u,
+            *self.q + self.v,
+            *self.q + self.u + self.v,
+        ];
+        Some(Aabb::from_points(&corners).padded(1e-4))
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::{Float, Vec3};
+
+    use crate::raytracer::{
+        geometry::{Direction, Point},
+        hit_info::Hittable,
+        material::MaterialKind,
+        quad::Quad,
+        radiance::Rgb,
+        ray::Ray,
+    };
+
+    #[test]
+    fn test_quad_hit_uv() {
+        let material = MaterialKind::Lambertian {
+            albedo: Rgb::white(),
+        };
+        let quad = Quad::new(
+            Point::new(-1., -1., -2.),
+            Vec3::new(2., 0., 0.),
+            Vec3::new(0., 2., 0.),
+            material,
+        );
+
+        let ray = Ray::new(Point::new(0., 0., 0.), Direction::new(0., 0., -1.));
+        let hit = quad.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!((hit.t - 2.).abs() < 1e-6);
+        assert!((hit.u - 0.5).abs() < 1e-6);
+        assert!((hit.v - 0.5).abs() < 1e-6);
+        assert!(hit.front_face);
+
+        let ray = Ray::new(Point::new(1.5, 0., 0.), Direction::new(0., 0., -1.));
+        assert!(quad.hit(&ray, 0.001, Float::INFINITY).is_none());
+    }
+}
@@ -0,0 +1,182 @@
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Sub};
+
+use super::{color::Color, math::Float};
+
+/// Linear RGB triple used for radiance, throughput and reflectance. In spectral
+/// mode the three channels hold values at the sampled wavelengths instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Rgb {
+    pub r: Float,
+    pub g: Float,
+    pub b: Float,
+}
+
+impl Rgb {
+    pub const fn new(r: Float, g: Float, b: Float) -> Self {
+        Self { r, g, b }
+    }
+
+    pub const fn splat(v: Float) -> Self {
+        Self::new(v, v, v)
+    }
+
+    pub const fn black() -> Self {
+        Self::splat(0.)
+    }
+
+    pub const fn white() -> Self {
+        Self::splat(1.)
+    }
+
+    pub fn luminance(self) -> Float {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
+    pub fn max_component(self) -> Float {
+        self.r.max(self.g).max(self.b)
+    }
+
+    pub fn sum(self) -> Float {
+        self.r + self.g + self.b
+    }
+
+    pub fn average(self) -> Float {
+        self.sum() / 3.
+    }
+
+    pub fn is_black(self) -> bool {
+        self.r == 0. && self.g == 0. && self.b == 0.
+    }
+
+    /// False if any channel is NaN or infinite.
+    pub fn is_finite(self) -> bool {
+        self.r.is_finite() && self.g.is_finite() && self.b.is_finite()
+    }
+
+    pub fn has_nan(self) -> bool {
+        self.r.is_nan() || self.g.is_nan() || self.b.is_nan()
+    }
+
+    pub fn clamp(self, min: Float, max: Float) -> Self {
+        self.map(|c| c.clamp(min, max))
+    }
+
+    pub fn lerp(self, other: Self, t: Float) -> Self {
+        self + (other - self) * t
+    }
+
+    pub fn map(self, f: impl Fn(Float) -> Float) -> Self {
+        Self::new(f(self.r), f(self.g), f(self.b))
+    }
+
+    /// Opaque color for display, without tone mapping.
+    pub fn to_color(self) -> Color {
+        Color::new(self.r, self.g, self.b, 1.)
+    }
+}
+
+impl From<Color> for Rgb {
+    /// Drops the alpha channel.
+    fn from(c: Color) -> Self {
+        Self::new(c.r, c.g, c.b)
+    }
+}
+
+impl From<[Float; 3]> for Rgb {
+    fn from([r, g, b]: [Float; 3]) -> Self {
+        Self::new(r, g, b)
+    }
+}
+
+impl Add for Rgb {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.r + rhs.r, self.g + rhs.g, self.b + rhs.b)
+    }
+}
+
+impl AddAssign for Rgb {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Rgb {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.r - rhs.r, self.g - rhs.g, self.b - rhs.b)
+    }
+}
+
+impl Mul for Rgb {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(self.r * rhs.r, self.g * rhs.g, self.b * rhs.b)
+    }
+}
+
+impl MulAssign for Rgb {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl Mul<Float> for Rgb {
+    type Output = Self;
+
+    fn mul(self, rhs: Float) -> Self {
+        self.map(|c| c * rhs)
+    }
+}
+
+impl Mul<Rgb> for Float {
+    type Output = Rgb;
+
+    fn mul(self, rhs: Rgb) -> Rgb {
+        rhs * self
+    }
+}
+
+impl MulAssign<Float> for Rgb {
+    fn mul_assign(&mut self, rhs: Float) {
+        *self = *self * rhs;
+    }
+}
+
+impl Div<Float> for Rgb {
+    type Output = Self;
+
+    fn div(self, rhs: Float) -> Self {
+        self.map(|c| c / rhs)
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::Float;
+    use crate::raytracer::radiance::Rgb;
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Rgb::new(1., 2., 3.);
+        let b = Rgb::splat(2.);
+        assert_eq!(a * b, Rgb::new(2., 4., 6.));
+        assert_eq!(a + b - b, a);
+        assert_eq!(a * 0.5, Rgb::new(0.5, 1., 1.5));
+        assert_eq!(a.lerp(b, 1.), b);
+        assert_eq!(a.clamp(0., 2.), Rgb::new(1., 2., 2.));
+        assert!((Rgb::white().luminance() - 1.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_finite_checks() {
+        assert!(Rgb::white().is_finite());
+        assert!(!Rgb::new(Float::NAN, 0., 0.).is_finite());
+        assert!(!Rgb::new(0., Float::INFINITY, 0.).is_finite());
+        assert!(Rgb::new(0., Float::NAN, 0.).has_nan());
+    }
+}
@@ -1,25 +1,48 @@
-use macroquad::prelude::Vec3;
+use quad_rand::RandomRange;
 
-#[allow(dead_code, unused_variables)]
+use super::{
+    geometry::{Direction, Point},
+    math::Float,
+};
 
+#[allow(dead_code, unused_variables)]
+#[derive(Debug, Clone, Copy)]
 pub struct Ray {
-    pub origin: Vec3,
-    pub direction: Vec3,
+    pub origin: Point,
+    pub direction: Direction,
+    /// instant the ray is traced at, used by moving objects for motion blur
+    pub time: Float,
 }
 
 impl Ray {
-    pub fn new(origin: Vec3, direction: Vec3) -> Self {
-        Ray { origin, direction }
+    pub fn new(origin: Point, direction: Direction) -> Self {
+        Ray::with_time(origin, direction, 0.)
+    }
+
+    pub fn with_time(origin: Point, direction: Direction, time: Float) -> Self {
+        Ray {
+            origin,
+            direction,
+            time,
+        }
     }
 
-    pub fn at(&self, t: f32) -> Vec3 {
+    pub fn at(&self, t: Float) -> Point {
         self.origin + self.direction * t
     }
 
-    pub fn from_camera(camera: &super::camera::Camera, u: f32, v: f32) -> Self {
-        Ray::new(
-            camera.origin,
-            camera.lower_left_corner + camera.horizontal * u + camera.vertical * v - camera.origin,
+    pub fn from_camera(camera: &super::camera::Camera, u: Float, v: Float) -> Self {
+        let time = if camera.shutter_close > camera.shutter_open {
+            RandomRange::gen_range(camera.shutter_open, camera.shutter_close)
+        } else {
+            camera.shutter_open
+        };
+
+        let target = camera.lower_left_corner + camera.horizontal * u + camera.vertical * v;
+        Ray::with_time(
+            Point::from_vec3(camera.origin),
+            Direction::from_vec3(target - camera.origin),
+            time,
         )
     }
 }
@@ -27,14 +50,15 @@ impl Ray {
 // unit tests
 #[cfg(test)]
 mod tests {
-    use macroquad::prelude::Vec3;
-
-    use crate::raytracer::ray::Ray;
+    use crate::raytracer::{
+        geometry::{Direction, Point},
+        ray::Ray,
+    };
 
     #[test]
     fn test_ray_creation() {
-        let origin = Vec3::new(1., 2., 3.);
-        let direction = Vec3::new(4., 5., 6.);
+        let origin = Point::new(1., 2., 3.);
+        let direction = Direction::new(4., 5., 6.);
         let ray = Ray::new(origin, direction);
 
         assert_eq!(ray.origin.x, 1.);
@@ -48,13 +72,13 @@ mod tests {
 
     #[test]
     fn test_ray_values() {
-        let origin = Vec3::new(2., 3., 4.);
-        let direction = Vec3::new(1., 0., 0.);
+        let origin = Point::new(2., 3., 4.);
+        let direction = Direction::new(1., 0., 0.);
         let ray = Ray::new(origin, direction);
 
-        assert_eq!(ray.at(0.), Vec3::new(2., 3., 4.));
-        assert_eq!(ray.at(1.), Vec3::new(3., 3., 4.));
-        assert_eq!(ray.at(-1.), Vec3::new(1., 3., 4.));
-        assert_eq!(ray.at(2.5), Vec3::new(4.5, 3., 4.));
+        assert_eq!(ray.at(0.), Point::new(2., 3., 4.));
+        assert_eq!(ray.at(1.), Point::new(3., 3., 4.));
+        assert_eq!(ray.at(-1.), Point::new(1., 3., 4.));
+        assert_eq!(ray.at(2.5), Point::new(4.5, 3., 4.));
     }
 }
@@ -0,0 +1,186 @@
+use super::{color::Color, math::Float};
+
+use super::radiance::Rgb;
+
+/// How many samples `render_update` takes for each pixel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SamplingMode {
+    /// Always take the same number of samples per pixel.
+    Fixed(u32),
+    /// Keep sampling a pixel until its estimated error drops below a threshold.
+    Adaptive(AdaptiveSampling),
+}
+
+impl Default for SamplingMode {
+    fn default() -> Self {
+        SamplingMode::Fixed(10)
+    }
+}
+
+impl SamplingMode {
+    pub fn needs_more_samples(&self, stats: &PixelStats) -> bool {
+        match self {
+            Self::Fixed(samples) => stats.count() < *samples,
+            Self::Adaptive(adaptive) => adaptive.needs_more_samples(stats),
+        }
+    }
+
+    pub fn max_samples(&self) -> u32 {
+        match self {
+            Self::Fixed(samples) => *samples,
+            Self::Adaptive(adaptive) => adaptive.max_samples,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveSampling {
+    /// samples taken before the error estimate is trusted
+    pub min_samples: u32,
+    /// hard cap on samples per pixel
+    pub max_samples: u32,
+    /// standard error of the pixel mean (in luminance) below which a pixel is done
+    pub threshold: Float,
+}
+
+impl Default for AdaptiveSampling {
+    fn default() -> Self {
+        Self {
+            min_samples: 4,
+            max_samples: 64,
+            threshold: 0.01,
+        }
+    }
+}
+
+impl AdaptiveSampling {
+    pub fn needs_more_samples(&self, stats: &PixelStats) -> bool {
+        if stats.count() < self.min_samples {
+            return true;
+        }
+        if stats.count() >= self.max_samples {
+            return false;
+        }
+
+        stats.standard_error() > self.threshold
+    }
+}
+
+/// Running mean and variance of the samples of a single pixel,
+/// updated with Welford's online algorithm.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PixelStats {
+    count: u32,
+    mean: Rgb,
+    luminance_mean: Float,
+    luminance_m2: Float,
+}
+
+impl PixelStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, sample: Rgb) {
+        self.count += 1;
+        let n = self.count as Float;
+
+        self.mean += (sample - self.mean) / n;
+
+        let l = sample.luminance();
+        let delta = l - self.luminance_mean;
+        self.luminance_mean += delta / n;
+        self.luminance_m2 += delta * (l - self.luminance_mean);
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn mean(&self) -> Rgb {
+        self.mean
+    }
+
+    /// Unbiased sample variance of the pixel luminance.
+    pub fn variance(&self) -> Float {
+        if self.count < 2 {
+            return 0.;
+        }
+        self.luminance_m2 / (self.count - 1) as Float
+    }
+
+    /// Estimated error of the pixel mean.
+    pub fn standard_error(&self) -> Float {
+        if self.count == 0 {
+            return Float::INFINITY;
+        }
+        (self.variance() / self.count as Float).sqrt()
+    }
+}
+
+/// Maps a sample count to a blue (few samples) to red (many samples) color,
+/// used to visualise where adaptive sampling spent its effort.
+pub fn spp_heatmap(spp: u32, max_spp: u32) -> Color {
+    let t = if max_spp == 0 {
+        0.
+    } else {
+        (spp as Float / max_spp as Float).clamp(0., 1.)
+    };
+
+    if t < 0.5 {
+        let s = t * 2.;
+        Color::new(0., s, 1. - s, 1.)
+    } else {
+        let s = (t - 0.5) * 2.;
+        Color::new(s, 1. - s, 0., 1.)
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::Float;
+
+    use crate::raytracer::{
+        radiance::Rgb,
+        sampler::{AdaptiveSampling, PixelStats},
+    };
+
+    #[test]
+    fn test_pixel_stats_mean_and_variance() {
+        let mut stats = PixelStats::new();
+        stats.add(Rgb::black());
+        stats.add(Rgb::white());
+
+        assert_eq!(stats.count(), 2);
+        assert!((stats.mean().r - 0.5).abs() < 1e-6);
+        assert!((stats.variance() - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_adaptive_stops_on_flat_pixels() {
+        let settings = AdaptiveSampling::default();
+        let mut stats = PixelStats::new();
+
+        while settings.needs_more_samples(&stats) {
+            stats.add(Rgb::new(0.5, 0.7, 1.0));
+        }
+
+        assert_eq!(stats.count(), settings.min_samples);
+    }
+
+    #[test]
+    fn test_adaptive_respects_max_samples() {
+        let settings = AdaptiveSampling::default();
+        let mut stats = PixelStats::new();
+
+        let mut i = 0;
+        while settings.needs_more_samples(&stats) {
+            let v = (i % 2) as Float;
+            stats.add(Rgb::splat(v));
+            i += 1;
+        }
+
+        assert_eq!(stats.count(), settings.max_samples);
+    }
+}
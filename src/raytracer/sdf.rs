@@ -0,0 +1,356 @@
+use std::rc::Rc;
+
+use super::{
+    aabb::Aabb,
+    geometry::Normal,
+    hit_info::{HitInfo, Hittable},
+    material::MaterialKind,
+    math::{Float, Vec2, Vec3},
+    ray::Ray,
+};
+
+/// Signed distance function: negative inside, positive outside.
+/// Shapes are centered at the origin and combined with the operator methods.
+#[derive(Clone)]
+pub enum Sdf {
+    Sphere {
+        radius: Float,
+    },
+    Box {
+        half_extents: Vec3,
+    },
+    Torus {
+        major_radius: Float,
+        minor_radius: Float,
+    },
+    /// Distance estimator of the Mandelbulb fractal.
+    Mandelbulb {
+        power: Float,
+        iterations: u32,
+    },
+    Translate {
+        offset: Vec3,
+        sdf: Box<Sdf>,
+    },
+    Union(Box<Sdf>, Box<Sdf>),
+    Intersection(Box<Sdf>, Box<Sdf>),
+    Subtraction(Box<Sdf>, Box<Sdf>),
+    SmoothUnion {
+        a: Box<Sdf>,
+        b: Box<Sdf>,
+        k: Float,
+    },
+    /// Infinite repetition of the inner shape on a grid with the given period.
+    Repeat {
+        period: Vec3,
+        sdf: Box<Sdf>,
+    },
+    /// Rotation around the y axis by `k` radians per unit of height.
+    Twist {
+        k: Float,
+        sdf: Box<Sdf>,
+    },
+    Round {
+        radius: Float,
+        sdf: Box<Sdf>,
+    },
+    Custom(Rc<dyn Fn(Vec3) -> Float>),
+}
+
+impl Sdf {
+    pub fn sphere(radius: Float) -> Self {
+        Sdf::Sphere { radius }
+    }
+
+    pub fn cuboid(half_extents: Vec3) -> Self {
+        Sdf::Box { half_extents }
+    }
+
+    pub fn torus(major_radius: Float, minor_radius: Float) -> Self {
+        Sdf::Torus {
+            major_radius,
+            minor_radius,
+        }
+    }
+
+    pub fn mandelbulb(power: Float, iterations: u32) -> Self {
+        Sdf::Mandelbulb { power, iterations }
+    }
+
+    pub fn custom(f: impl Fn(Vec3) -> Float + 'static) -> Self {
+        Sdf::Custom(Rc::new(f))
+    }
+
+    pub fn translate(self, offset: Vec3) -> Self {
+        Sdf::Translate {
+            offset,
+            sdf: Box::new(self),
+        }
+    }
+
+    pub fn union(self, other: Sdf) -> Self {
+        Sdf::Union(Box::new(self), Box::new(other))
+    }
+
+    pub fn intersection(self, other: Sdf) -> Self {
+        Sdf::Intersection(Box::new(self), Box::new(other))
+    }
+
+    pub fn subtract(self, other: Sdf) -> Self {
+        Sdf::Subtraction(Box::new(self), Box::new(other))
+    }
+
+    pub fn smooth_union(self, other: Sdf, k: Float) -> Self {
+        Sdf::SmoothUnion {
+            a: Box::new(self),
+            b: Box::new(other),
+            k,
+        }
+    }
+
+    pub fn repeat(self, period: Vec3) -> Self {
+        Sdf::Repeat {
+            period,
+            sdf: Box::new(self),
+        }
+    }
+
+    pub fn twist(self, k: Float) -> Self {
+        Sdf::Twist {
+            k,
+            sdf: Box::new(self),
+        }
+    }
+
+    pub fn round(self, radius: Float) -> Self {
+        Sdf::Round {
+            radius,
+            sdf: Box::new(self),
+        }
+    }
+
+    pub fn distance(&self, p: Vec3) -> Float {
+        match self {
+            Self::Sphere { radius } => p.length() - radius,
+            Self::Box { half_extents } => {
+                let q = p.abs() - *half_extents;
+                q.max(Vec3::ZERO).length() + q.max_element().min(0.)
+            }
+            Self::Torus {
+                major_radius,
+                minor_radius,
+            } => {
+                let q = Vec2::new(Vec2::new(p.x, p.z).length() - major_radius, p.y);
+                q.length() - minor_radius
+            }
+            Self::Mandelbulb { power, iterations } => mandelbulb(p, *power, *iterations),
+            Self::Translate { offset, sdf } => sdf.distance(p - *offset),
+            Self::Union(a, b) => a.distance(p).min(b.distance(p)),
+            Self::Intersection(a, b) => a.distance(p).max(b.distance(p)),
+            Self::Subtraction(a, b) => a.distance(p).max(-b.distance(p)),
+            Self::SmoothUnion { a, b, k } => {
+                let d1 = a.distance(p);
+                let d2 = b.distance(p);
+                let h = (0.5 + 0.5 * (d2 - d1) / k).clamp(0., 1.);
+                d2 + (d1 - d2) * h - k * h * (1. - h)
+            }
+            Self::Repeat { period, sdf } => sdf.distance(p - *period * (p / *period).round()),
+            Self::Twist { k, sdf } => {
+                let (s, c) = (k * p.y).sin_cos();
+                let q = Vec3::new(c * p.x - s * p.z, p.y, s * p.x + c * p.z);
+                sdf.distance(q)
+            }
+            Self::Round { radius, sdf } => sdf.distance(p) - radius,
+            Self::Custom(f) => f(p),
+        }
+    }
+
+    /// Surface normal from the gradient of the distance field (tetrahedron technique).
+    pub fn normal(&self, p: Vec3, h: Float) -> Vec3 {
+        let k1 = Vec3::new(1., -1., -1.);
+        let k2 = Vec3::new(-1., -1., 1.);
+        let k3 = Vec3::new(-1., 1., -1.);
+        let k4 = Vec3::new(1., 1., 1.);
+
+        (k1 * self.distance(p + k1 * h)
+            + k2 * self.distance(p + k2 * h)
+            + k3 * self.distance(p + k3 * h)
+            + k4 * self.distance(p + k4 * h))
+        .normalize_or_zero()
+    }
+}
+
+fn mandelbulb(p: Vec3, power: Float, iterations: u32) -> Float {
+    let mut z = p;
+    let mut dr = 1.;
+    let mut r = 0.;
+
+    for _ in 0..iterations {
+        r = z.length();
+        if r > 2. {
+            break;
+        }
+
+        let theta = (z.z / r).acos() * power;
+        let phi = z.y.atan2(z.x) * power;
+        dr = r.powf(power - 1.) * power * dr + 1.;
+
+        let zr = r.powf(power);
+        z =
+            zr * Vec3::new(
+                theta.sin() * phi.cos(),
+                phi.sin() * theta.sin(),
+                theta.cos(),
+            ) + p;
+    }
+
+    0.5 * r.ln() * r / dr
+}
+
+/// Sphere-traced distance field placed in the scene.
+pub struct SdfObject {
+    pub sdf: Sdf,
+    pub material: MaterialKind,
+    /// Region the field is marched in; `None` marches up to `max_distance`.
+    pub bounds: Option<Aabb>,
+    pub max_steps: u32,
+    pub max_distance: Float,
+    pub epsilon: Float,
+    /// Fraction of the distance bound taken at each step; lower it for fields that
+    /// underestimate poorly, like twisted shapes.
+    pub step_scale: Float,
+}
+
+impl SdfObject {
+    pub fn new(sdf: Sdf, material: MaterialKind) -> Self {
+        Self {
+            sdf,
+            material,
+            bounds: None,
+            max_steps: 256,
+            max_distance: 100.,
+            epsilon: 1e-4,
+            step_scale: 1.,
+        }
+    }
+
+    pub fn with_bounds(mut self, bounds: Aabb) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    pub fn with_step_scale(mut self, step_scale: Float) -> Self {
+        self.step_scale = step_scale;
+        self
+    }
+}
+
+impl Hittable for SdfObject {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitInfo> {
+        let (t_start, t_end) = match &self.bounds {
+            Some(bounds) => bounds.clip(ray, t_min, t_max)?,
+            None => (t_min, t_max),
+        };
+
+        // march in unit-length steps along the ray, converting back to ray parameter on hit;
+        // without bounds, queries over the whole ray line start `max_distance` behind the origin
+        let length = ray.direction.length();
+        let direction = ray.direction / length;
+        let mut s = (t_start.max(t_min) * length).max(-self.max_distance);
+        let s_end = (t_end * length).min(self.max_distance);
+
+        for _ in 0..self.max_steps {
+            if s > s_end {
+                return None;
+            }
+
+            let p = ray.origin + direction * s;
+            let d = self.sdf.distance(*p);
+            if d.abs() < self.epsilon && s > t_min * length {
+                let t = s / length;
+                let normal = Normal::from_vec3(self.sdf.normal(*p, self.epsilon));
+                // the marcher stops anywhere within epsilon of the surface, on either side
+                let p_error = Vec3::splat(2. * self.epsilon);
+                let mut hit_info = HitInfo::new(true, t, p, p_error, normal, self.material.clone());
+                hit_info.set_face_normal(ray.direction, normal);
+                return Some(hit_info);
+            }
+
+            s += d.abs().max(self.epsilon) * self.step_scale;
+        }
+
+        None
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bounds
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::{Float, Vec3};
+
+    use crate::raytracer::{
+        constant_medium::ConstantMedium,
+        geometry::{Direction, Point},
+        hit_info::Hittable,
+        material::MaterialKind,
+        radiance::Rgb,
+        ray::Ray,
+        sdf::{Sdf, SdfObject},
+    };
+
+    #[test]
+    fn test_sdf_distances() {
+        let sphere = Sdf::sphere(1.);
+        assert!((sphere.distance(Vec3::new(2., 0., 0.)) - 1.).abs() < 1e-6);
+
+        let cube = Sdf::cuboid(Vec3::splat(1.));
+        assert!((cube.distance(Vec3::new(0., 3., 0.)) - 2.).abs() < 1e-6);
+        assert!(cube.distance(Vec3::ZERO) < 0.);
+
+        let repeated = Sdf::sphere(0.5).repeat(Vec3::splat(4.));
+        assert!(repeated.distance(Vec3::new(8., 4., -4.)) < 0.);
+
+        let blob = Sdf::sphere(1.).smooth_union(Sdf::sphere(1.).translate(Vec3::X * 2.), 0.5);
+        assert!(blob.distance(Vec3::new(1., 0., 0.)) < 0.);
+
+        let custom = Sdf::custom(|p| p.y);
+        assert_eq!(custom.distance(Vec3::new(3., 2., 1.)), 2.);
+    }
+
+    #[test]
+    fn test_sdf_sphere_matches_analytic_hit() {
+        let material = MaterialKind::Lambertian {
+            albedo: Rgb::white(),
+        };
+        let object = SdfObject::new(Sdf::sphere(1.), material);
+
+        let ray = Ray::new(Point::new(0., 0., -5.), Direction::new(0., 0., 2.));
+        let hit = object.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!((hit.t - 2.).abs() < 1e-3);
+        assert!((*hit.normal - Vec3::new(0., 0., -1.)).length() < 1e-2);
+
+        let ray = Ray::new(Point::new(0., 2., -5.), Direction::new(0., 0., 1.));
+        assert!(object.hit(&ray, 0.001, Float::INFINITY).is_none());
+    }
+    #[test]
+    fn test_unbounded_sdf_as_medium_boundary() {
+        let material = MaterialKind::Lambertian {
+            albedo: Rgb::white(),
+        };
+        let boundary = SdfObject::new(Sdf::sphere(1.), material);
+        let fog = ConstantMedium::new(Box::new(boundary), 100., Rgb::white());
+
+        // the medium looks for the boundary along the whole ray line
+        for origin in [Point::new(0., 0., -5.), Point::ZERO] {
+            let ray = Ray::new(origin, Direction::new(0., 0., 1.));
+            for _ in 0..100 {
+                let hit = fog.hit(&ray, 0.001, Float::INFINITY).unwrap();
+                assert!(hit.p.length() < 1. + 1e-3);
+            }
+        }
+    }
+}
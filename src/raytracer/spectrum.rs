@@ -0,0 +1,190 @@
+//! Spectral rendering support. In spectral mode every path carries radiance at
+//! three sampled wavelengths in the channels of an `Rgb` rather than red, green and blue.
+
+use std::sync::OnceLock;
+
+use quad_rand::RandomRange;
+
+use super::{
+    math::{Float, Vec3},
+    radiance::Rgb,
+};
+
+pub const LAMBDA_MIN: Float = 380.;
+pub const LAMBDA_MAX: Float = 720.;
+
+/// Wavelengths (in nm) carried by one path, one per color channel.
+/// The first one is the hero wavelength, the others are evenly rotated from it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampledWavelengths {
+    pub lambda: [Float; 3],
+    pub pdf: [Float; 3],
+}
+
+impl SampledWavelengths {
+    pub fn sample_uniform(u: Float) -> Self {
+        let range = LAMBDA_MAX - LAMBDA_MIN;
+        let lambda = [0., 1., 2.].map(|i: Float| LAMBDA_MIN + (u + i / 3.).fract() * range);
+        Self {
+            lambda,
+            pdf: [1. / range; 3],
+        }
+    }
+
+    pub fn random() -> Self {
+        Self::sample_uniform(RandomRange::gen_range(0., 1.))
+    }
+
+    /// RGB reflectance or radiance `c` evaluated at each of the wavelengths.
+    pub fn upsample(&self, c: Rgb) -> Rgb {
+        let [a, b, d] = self.lambda.map(|l| rgb_to_spectrum(c, l));
+        Rgb::new(a, b, d)
+    }
+
+    /// Per-wavelength values of a quantity known at the red, green and blue
+    /// reference wavelengths, such as a measured index of refraction.
+    pub fn interpolate(&self, c: Rgb) -> Rgb {
+        let [a, b, d] = self.lambda.map(|l| interpolate_rgb_samples(c, l));
+        Rgb::new(a, b, d)
+    }
+
+    /// Converts the radiance carried at the sampled wavelengths to linear sRGB,
+    /// white balanced so that a constant spectrum of 1 maps to white.
+    pub fn to_rgb(self, radiance: Rgb) -> Rgb {
+        let values = [radiance.r, radiance.g, radiance.b];
+        let mut xyz = Vec3::ZERO;
+        for ((lambda, pdf), value) in self.lambda.iter().zip(self.pdf).zip(values) {
+            if pdf > 0. {
+                xyz += cie_xyz(*lambda) * (value / pdf);
+            }
+        }
+        let rgb = xyz_to_linear_srgb(xyz / 3.) / white_rgb();
+        Rgb::new(rgb.x, rgb.y, rgb.z)
+    }
+}
+
+/// Keeps only the hero wavelength, for events such as dispersion where the other
+/// wavelengths would follow different paths. The hero channel is scaled so the
+/// estimate stays unbiased.
+pub fn terminate_secondary(c: Rgb) -> Rgb {
+    Rgb::new(c.r * 3., 0., 0.)
+}
+
+fn smoothstep(x: Float, edge0: Float, edge1: Float) -> Float {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0., 1.);
+    t * t * (3. - 2. * t)
+}
+
+/// Smooth spectrum for an RGB value, built from blue, green and red basis curves
+/// that sum to one, so white stays a flat spectrum and reflectances stay in [0, 1].
+pub fn rgb_to_spectrum(c: Rgb, lambda: Float) -> Float {
+    let blue = 1. - smoothstep(lambda, 480., 510.);
+    let red = smoothstep(lambda, 575., 605.);
+    let green = 1. - blue - red;
+    c.r * red + c.g * green + c.b * blue
+}
+
+/// Piecewise linear interpolation of values given at 650 (red), 550 (green)
+/// and 450 nm (blue), clamped outside that range.
+pub fn interpolate_rgb_samples(c: Rgb, lambda: Float) -> Float {
+    if lambda <= 450. {
+        c.b
+    } else if lambda <= 550. {
+        let t = (lambda - 450.) / 100.;
+        c.b + (c.g - c.b) * t
+    } else if lambda <= 650. {
+        let t = (lambda - 550.) / 100.;
+        c.g + (c.r - c.g) * t
+    } else {
+        c.r
+    }
+}
+
+fn piecewise_gaussian(x: Float, mu: Float, sigma_low: Float, sigma_high: Float) -> Float {
+    let sigma = if x < mu { sigma_low } else { sigma_high };
+    let t = (x - mu) / sigma;
+    (-0.5 * t * t).exp()
+}
+
+/// CIE 1931 color matching functions, using the multi-lobe fit from
+/// Wyman et al. 2013, "Simple Analytic Approximations to the CIE XYZ Color Matching Functions".
+pub fn cie_xyz(lambda: Float) -> Vec3 {
+    let x = 1.056 * piecewise_gaussian(lambda, 599.8, 37.9, 31.0)
+        + 0.362 * piecewise_gaussian(lambda, 442.0, 16.0, 26.7)
+        - 0.065 * piecewise_gaussian(lambda, 501.1, 20.4, 26.2);
+    let y = 0.821 * piecewise_gaussian(lambda, 568.8, 46.9, 40.5)
+        + 0.286 * piecewise_gaussian(lambda, 530.9, 16.3, 31.1);
+    let z = 1.217 * piecewise_gaussian(lambda, 437.0, 11.8, 36.0)
+        + 0.681 * piecewise_gaussian(lambda, 459.0, 26.0, 13.8);
+    Vec3::new(x, y, z)
+}
+
+pub fn xyz_to_linear_srgb(xyz: Vec3) -> Vec3 {
+    Vec3::new(
+        3.2406 * xyz.x - 1.5372 * xyz.y - 0.4986 * xyz.z,
+        -0.9689 * xyz.x + 1.8758 * xyz.y + 0.0415 * xyz.z,
+        0.0557 * xyz.x - 0.2040 * xyz.y + 1.0570 * xyz.z,
+    )
+}
+
+/// sRGB of a constant unit spectrum over the sampled range.
+fn white_rgb() -> Vec3 {
+    static WHITE: OnceLock<Vec3> = OnceLock::new();
+    *WHITE.get_or_init(|| {
+        let mut xyz = Vec3::ZERO;
+        let mut lambda = LAMBDA_MIN + 0.5;
+        while lambda < LAMBDA_MAX {
+            xyz += cie_xyz(lambda);
+            lambda += 1.;
+        }
+        xyz_to_linear_srgb(xyz)
+    })
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::Float;
+
+    use crate::raytracer::{
+        radiance::Rgb,
+        spectrum::{rgb_to_spectrum, SampledWavelengths},
+    };
+
+    #[test]
+    fn test_white_round_trips() {
+        let white = Rgb::white();
+        for l in [400., 500., 600., 700.] {
+            assert!((rgb_to_spectrum(white, l) - 1.).abs() < 1e-6);
+        }
+
+        // averaging many wavelength samples of a flat spectrum gives white
+        let n = 4000;
+        let mut sum = [0.; 3];
+        for i in 0..n {
+            let wavelengths = SampledWavelengths::sample_uniform((i as Float + 0.5) / n as Float);
+            let rgb = wavelengths.to_rgb(wavelengths.upsample(white));
+            sum[0] += rgb.r / n as Float;
+            sum[1] += rgb.g / n as Float;
+            sum[2] += rgb.b / n as Float;
+        }
+        for c in sum {
+            assert!((c - 1.).abs() < 0.02, "{sum:?}");
+        }
+    }
+
+    #[test]
+    fn test_primaries_keep_their_hue() {
+        let n = 4000;
+        let red = Rgb::new(1., 0., 0.);
+        let mut sum = [0.; 3];
+        for i in 0..n {
+            let wavelengths = SampledWavelengths::sample_uniform((i as Float + 0.5) / n as Float);
+            let rgb = wavelengths.to_rgb(wavelengths.upsample(red));
+            sum[0] += rgb.r;
+            sum[1] += rgb.g;
+            sum[2] += rgb.b;
+        }
+        assert!(sum[0] > sum[1] && sum[0] > sum[2]);
+    }
+}
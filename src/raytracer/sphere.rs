@@ -1,66 +1,91 @@
-use macroquad::prelude::Vec3;
-
 use super::{
-    hit_info::Hittable,
-    material::{Material, MaterialKind},
+    aabb::Aabb,
+    geometry::{gamma, Direction, Normal, Point},
+    hit_info::{HitInfo, Hittable},
+    material::MaterialKind,
+    math::{consts::PI, Float, Vec3},
+    ray::Ray,
 };
 
 pub struct Sphere {
-    pub center: Vec3,
-    pub radius: f32,
+    pub center: Point,
+    pub radius: Float,
     pub material: MaterialKind,
 }
 
 impl Sphere {
-    pub fn new(center: Vec3, radius: f32, material: MaterialKind) -> Self {
+    pub fn new(center: Point, radius: Float, material: MaterialKind) -> Self {
         Self {
             center,
             radius,
             material,
         }
     }
+
+    /// Derivatives `(dp/du, dp/dv)` of the `uv` parameterization at a point `p`
+    /// on the unit sphere, for a sphere of the given radius.
+    pub fn tangents(p: Vec3, radius: Float) -> (Vec3, Vec3) {
+        let sin_theta = (1. - p.y * p.y).max(1e-8).sqrt();
+        let dpdu = 2. * PI * radius * Vec3::new(p.z, 0., -p.x);
+        let dpdv =
+            PI * radius * Vec3::new(-p.x * p.y / sin_theta, sin_theta, -p.y * p.z / sin_theta);
+        (dpdu, dpdv)
+    }
+
+    fn hit_at(&self, ray: &Ray, t: Float) -> HitInfo {
+        // reproject onto the surface, so the point only carries the error of
+        // this step rather than that of the intersection
+        let offset = *(ray.at(t) - self.center);
+        let offset = offset * (self.radius.abs() / offset.length());
+        let p = self.center + Direction::from_vec3(offset);
+        let normal = Normal::from_vec3(offset / self.radius);
+
+        let p_error = gamma(6) * (offset.abs() + self.center.abs());
+        let mut hit_info = HitInfo::new(true, t, p, p_error, normal, self.material.clone());
+        hit_info.set_face_normal(ray.direction, normal);
+        let unit = offset / self.radius.abs();
+        let (u, v) = Sphere::uv(unit);
+        hit_info.set_uv(u, v);
+        let (dpdu, dpdv) = Sphere::tangents(unit, self.radius.abs());
+        hit_info.set_tangents(Direction::from_vec3(dpdu), Direction::from_vec3(dpdv));
+        hit_info
+    }
+
+    /// Texture coordinates of a point `p` on the unit sphere centered at the origin.
+    pub fn uv(p: Vec3) -> (Float, Float) {
+        let theta = (-p.y).acos();
+        let phi = (-p.z).atan2(p.x) + PI;
+
+        (phi / (2. * PI), theta / PI)
+    }
 }
 
 impl Hittable for Sphere {
-    fn hit(
-        &self,
-        ray: &super::ray::Ray,
-        t_min: f32,
-        t_max: f32,
-    ) -> Option<super::hit_info::HitInfo> {
-        let oc = ray.origin - self.center;
-        let a = ray.direction.dot(ray.direction);
-        let b = 2. * oc.dot(ray.direction);
-        let c = oc.dot(oc) - self.radius * self.radius;
-        let discriminant = b * b - 4. * a * c;
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitInfo> {
+        let oc = *(ray.origin - self.center);
+        let a = ray.direction.length_squared();
+        let half_b = oc.dot(*ray.direction);
+        let c = oc.length_squared() - self.radius * self.radius;
+        let discriminant = half_b * half_b - a * c;
 
         if discriminant < 0. {
             return None;
         }
 
-        let t = (-b - discriminant.sqrt()) / (2. * a);
-        if t < t_max && t > t_min {
-            let p = ray.at(t);
-
-            let normal = (p - self.center) / self.radius;
-
-            let mut hit_info = super::hit_info::HitInfo::new(true, t, p, normal, self.material);
-            hit_info.set_face_normal(ray.direction, normal);
-            // normal.y = -normal.y;
-            return Some(hit_info);
-        }
-
-        let t = (-b + discriminant.sqrt()) / (2. * a);
-        if t < t_max && t > t_min {
-            let p = ray.at(t);
-            let normal = (p - self.center) / self.radius;
-
-            let mut hit_info = super::hit_info::HitInfo::new(true, t, p, normal, self.material);
-            hit_info.set_face_normal(ray.direction, normal);
+        // avoids the cancellation in -b ± sqrt(discriminant) for the root close
+        // to zero, which is the one rays leaving the surface must not find
+        let q = -(half_b + half_b.signum() * discriminant.sqrt());
+        let (t0, t1) = (q / a, c / q);
+        let (t0, t1) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
 
-            return Some(hit_info);
-        }
+        [t0, t1]
+            .into_iter()
+            .find(|t| *t < t_max && *t > t_min)
+            .map(|t| self.hit_at(ray, t))
+    }
 
-        None
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = Vec3::splat(self.radius.abs());
+        Some(Aabb::new(*self.center - r, *self.center + r))
     }
 }
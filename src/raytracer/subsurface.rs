@@ -0,0 +1,180 @@
+//! Random-walk subsurface scattering: light refracts into the object, performs a
+//! volumetric random walk inside it and leaves through the boundary elsewhere.
+
+use quad_rand::RandomRange;
+
+use super::{
+    bsdf::{BsdfSample, ShadingFrame},
+    dielectric::Dielectric,
+    geometry::Direction,
+    hit_info::Hittable,
+    material::sample_henyey_greenstein,
+    math::{Float, Vec3},
+    radiance::Rgb,
+    ray::Ray,
+    spectrum::SampledWavelengths,
+};
+
+/// Scattering events after which a walk is considered absorbed.
+const MAX_WALK_STEPS: usize = 256;
+
+/// Translucent material such as skin, wax or marble. Must be used on closed objects.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Subsurface {
+    /// multiple scattering albedo, i.e. the color of the material seen from afar
+    pub albedo: Rgb,
+    /// average distance light travels inside the material, per channel
+    pub mean_free_path: Rgb,
+    /// Henyey-Greenstein asymmetry of the interior phase function
+    pub g: Float,
+    /// boundary of the object
+    pub surface: Dielectric,
+}
+
+impl Subsurface {
+    pub fn new(albedo: Rgb, mean_free_path: Rgb) -> Self {
+        Self {
+            albedo,
+            mean_free_path,
+            g: 0.,
+            surface: Dielectric::new(1.4, 0.),
+        }
+    }
+
+    pub fn with_ior(mut self, ior: Float) -> Self {
+        self.surface.ior = ior;
+        self
+    }
+
+    pub fn with_roughness(mut self, roughness: Float) -> Self {
+        self.surface.roughness = roughness;
+        self
+    }
+
+    pub fn with_anisotropy(mut self, g: Float) -> Self {
+        self.g = g;
+        self
+    }
+
+    /// Copy with the albedo and mean free path evaluated at the sampled wavelengths.
+    pub fn at_wavelengths(&self, wavelengths: &SampledWavelengths) -> Self {
+        Self {
+            albedo: wavelengths.upsample(self.albedo),
+            mean_free_path: wavelengths.upsample(self.mean_free_path),
+            surface: self.surface.at_wavelengths(wavelengths),
+            ..*self
+        }
+    }
+
+    /// Extinction and single scattering albedo of the interior medium, per channel,
+    /// fitted so the walk reproduces `albedo` and `mean_free_path`
+    /// (Chiang et al. 2016, "Practical and Controllable Subsurface Scattering").
+    pub fn coefficients(&self) -> (Rgb, Rgb) {
+        let albedo = self.albedo.clamp(0., 0.999);
+        let s = albedo.map(|a| 1.9 - a + 3.5 * (a - 0.8) * (a - 0.8));
+        let sigma_t = (self.mean_free_path * s).map(|x| 1. / x.max(1e-6));
+        let single_albedo =
+            albedo.map(|a| 1. - (a * (-5.09406 + a * (2.61188 - a * 4.31805))).exp());
+        (sigma_t, single_albedo)
+    }
+
+    /// Samples the boundary, which either reflects or refracts into the object.
+    pub fn sample(&self, wo: Vec3, frame: ShadingFrame, front_face: bool) -> Option<BsdfSample> {
+        self.surface.sample(wo, frame, front_face)
+    }
+
+    /// Follows a ray that has just refracted into `object` until it leaves
+    /// through the boundary, returning the ray leaving the surface and the
+    /// throughput of the walk, or `None` if the light was absorbed. Other
+    /// geometry inside the object is ignored.
+    pub fn random_walk(&self, object: &dyn Hittable, ray: &Ray) -> Option<(Ray, Rgb)> {
+        let (sigma_t, single_albedo) = self.coefficients();
+        let mut throughput = Rgb::white();
+        let mut origin = ray.origin;
+        let mut direction = ray.direction.normalize();
+
+        for _ in 0..MAX_WALK_STEPS {
+            // spectral MIS: pick the channel to sample distances with in
+            // proportion to its throughput
+            let sum = throughput.sum();
+            if sum <= 0. {
+                return None;
+            }
+            let channel_pdf = throughput / sum;
+            let xi = RandomRange::gen_range(0., 1.);
+            let sigma = if xi < channel_pdf.r {
+                sigma_t.r
+            } else if xi < channel_pdf.r + channel_pdf.g {
+                sigma_t.g
+            } else {
+                sigma_t.b
+            };
+
+            let distance = -(1. - RandomRange::gen_range(0. as Float, 1.)).ln() / sigma;
+            let segment = Ray::with_time(origin, direction, ray.time);
+
+            if let Some(hit) = object.hit(&segment, 0., distance) {
+                let transmittance = sigma_t.map(|s| (-s * hit.t).exp());
+                throughput *= transmittance / (channel_pdf * transmittance).sum();
+
+                // the boundary either lets the light out or reflects it back inside
+                let wo = -*direction;
+                let frame = ShadingFrame::from_hit(&hit);
+                let sample = self.surface.sample(wo, frame, hit.front_face)?;
+                throughput *= sample.weight(frame.n);
+
+                let next = hit.spawn_ray(Direction::from_vec3(sample.wi), ray.time);
+                if sample.wi.dot(*hit.normal) < 0. {
+                    return Some((next, throughput));
+                }
+                origin = next.origin;
+                direction = next.direction;
+                continue;
+            }
+
+            let transmittance = sigma_t.map(|s| (-s * distance).exp());
+            let pdf = (channel_pdf * sigma_t * transmittance).sum();
+            throughput *= single_albedo * sigma_t * transmittance / pdf;
+
+            origin = segment.at(distance);
+            direction = Direction::from_vec3(sample_henyey_greenstein(*direction, self.g));
+        }
+
+        None
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::Float;
+
+    use crate::raytracer::{
+        geometry::{Direction, Point},
+        material::MaterialKind,
+        radiance::Rgb,
+        ray::Ray,
+        sphere::Sphere,
+        subsurface::Subsurface,
+    };
+
+    #[test]
+    fn test_random_walk_exits_sphere() {
+        let material = Subsurface::new(Rgb::new(0.8, 0.5, 0.3), Rgb::new(0.1, 0.1, 0.1));
+        let sphere = Sphere::new(Point::ZERO, 1., MaterialKind::Subsurface(material));
+
+        let n = 2000;
+        let mut escaped = 0.;
+        for _ in 0..n {
+            let ray = Ray::new(Point::new(0., 0., 1.), Direction::new(0., 0., -1.));
+            if let Some((out, throughput)) = material.random_walk(&sphere, &ray) {
+                assert!((out.origin.length() - 1.).abs() < 1e-3);
+                assert!(out.direction.dot(*out.origin) > 0.);
+                escaped += throughput.g;
+            }
+        }
+        // the walk loses energy only to absorption
+        let albedo = escaped / n as Float;
+        assert!(albedo > 0.2 && albedo < 0.8, "albedo {albedo}");
+    }
+}
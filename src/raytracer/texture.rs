@@ -0,0 +1,135 @@
+use std::{io, path::Path, rc::Rc};
+
+#[cfg(feature = "app")]
+use macroquad::texture::Image;
+
+use super::{color::Color, geometry::Point, math::Float};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Texture {
+    Solid(Color),
+    /// Alternating squares of side `1 / scale` in texture space.
+    Checker {
+        even: Color,
+        odd: Color,
+        scale: Float,
+    },
+    /// Bitmap repeated over texture space, one copy per unit square.
+    Image(Rc<ImageTexture>),
+}
+
+impl Texture {
+    pub fn value(&self, u: Float, v: Float, _p: Point) -> Color {
+        match self {
+            Self::Solid(color) => *color,
+            Self::Checker { even, odd, scale } => {
+                let i = (u * scale).floor() as i64 + (v * scale).floor() as i64;
+                if i % 2 == 0 {
+                    *even
+                } else {
+                    *odd
+                }
+            }
+            Self::Image(image) => image.sample(u, v),
+        }
+    }
+}
+
+/// Bilinearly filtered RGBA bitmap. Values are used as stored, so color
+/// textures should be converted to linear space before use.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageTexture {
+    width: usize,
+    height: usize,
+    data: Vec<[Float; 4]>,
+}
+
+impl ImageTexture {
+    pub fn new(width: usize, height: usize, data: Vec<[Float; 4]>) -> Self {
+        assert_eq!(data.len(), width * height, "texture size mismatch");
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    #[cfg(feature = "app")]
+    pub fn from_image(image: &Image) -> Self {
+        let data = image
+            .get_image_data()
+            .iter()
+            .map(|p| p.map(|c| c as Float / 255.))
+            .collect();
+        Self::new(image.width(), image.height(), data)
+    }
+
+    /// Loads a png or tga file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::decode(&std::fs::read(path)?)
+    }
+
+    pub fn decode(bytes: &[u8]) -> io::Result<Self> {
+        let image = image::load_from_memory(bytes)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+            .to_rgba8();
+        let data = image
+            .pixels()
+            .map(|p| p.0.map(|c| c as Float / 255.))
+            .collect();
+        Ok(Self::new(
+            image.width() as usize,
+            image.height() as usize,
+            data,
+        ))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn texel(&self, x: i64, y: i64) -> [Float; 4] {
+        let x = x.rem_euclid(self.width as i64) as usize;
+        let y = y.rem_euclid(self.height as i64) as usize;
+        self.data[y * self.width + x]
+    }
+
+    /// Color at `(u, v)`, with `v = 0` at the bottom row of the image.
+    pub fn sample(&self, u: Float, v: Float) -> Color {
+        let x = u * self.width as Float - 0.5;
+        let y = (1. - v) * self.height as Float - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let mut rgba = [0.; 4];
+        for (dx, dy, w) in [
+            (0, 0, (1. - fx) * (1. - fy)),
+            (1, 0, fx * (1. - fy)),
+            (0, 1, (1. - fx) * fy),
+            (1, 1, fx * fy),
+        ] {
+            let texel = self.texel(x0 + dx, y0 + dy);
+            for (c, t) in rgba.iter_mut().zip(texel) {
+                *c += w * t;
+            }
+        }
+        Color::from(rgba)
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::texture::ImageTexture;
+
+    #[test]
+    fn test_decode_rejects_corrupt_data() {
+        let err = ImageTexture::decode(b"\x89PNG\r\n\x1a\nnot really a png").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
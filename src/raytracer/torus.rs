@@ -0,0 +1,135 @@
+use super::{
+    aabb::Aabb,
+    geometry::{ray_point_error, Direction, Frame, Normal, Point},
+    hit_info::{HitInfo, Hittable},
+    material::MaterialKind,
+    math::{consts::PI, Float, Vec3},
+    polynomial::{narrow, narrow_vec3, solve_quartic, widen, widen_vec3},
+    ray::Ray,
+};
+
+/// Torus around `axis`: a tube of `minor_radius` swept along a circle of `major_radius`.
+pub struct Torus {
+    pub major_radius: Float,
+    pub minor_radius: Float,
+    pub material: MaterialKind,
+    frame: Frame,
+}
+
+impl Torus {
+    pub fn new(
+        center: Point,
+        axis: Vec3,
+        major_radius: Float,
+        minor_radius: Float,
+        material: MaterialKind,
+    ) -> Self {
+        Self {
+            major_radius,
+            minor_radius,
+            material,
+            frame: Frame::new(center, axis),
+        }
+    }
+}
+
+impl Hittable for Torus {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitInfo> {
+        let o = widen_vec3(*self.frame.local_point(ray.origin));
+        let d = widen_vec3(*self.frame.local_direction(ray.direction));
+        let r2 = widen(self.major_radius).powi(2);
+        let s2 = widen(self.minor_radius).powi(2);
+
+        // (|o + td|^2 - R^2 - r^2)^2 = 4R^2 (r^2 - (o.y + t d.y)^2)
+        let dd = d.dot(d);
+        let e = o.dot(o) - r2 - s2;
+        let f = o.dot(d);
+        let four_r2 = 4. * r2;
+
+        let roots = solve_quartic([
+            e * e - four_r2 * (s2 - o.y * o.y),
+            4. * f * e + 2. * four_r2 * o.y * d.y,
+            2. * dd * e + 4. * f * f + four_r2 * d.y * d.y,
+            4. * dd * f,
+            dd * dd,
+        ]);
+
+        let t = roots
+            .into_iter()
+            .map(narrow)
+            .find(|t| *t > t_min && *t < t_max)?;
+
+        let p = narrow_vec3(o + d * widen(t));
+        let ring = Vec3::new(p.x, 0., p.z).normalize_or_zero() * self.major_radius;
+        let local_normal = (p - ring).normalize();
+
+        let rho = Vec3::new(p.x, 0., p.z).length();
+        let u = (-p.z).atan2(p.x) / (2. * PI) + 0.5;
+        let v = p.y.atan2(rho - self.major_radius) / (2. * PI) + 0.5;
+        // u sweeps around the axis, v around the tube
+        let dpdu = 2. * PI * Vec3::new(p.z, 0., -p.x);
+        let rho = rho.max(1e-8);
+        let dpdv = 2. * PI * Vec3::new(-p.y * p.x / rho, rho - self.major_radius, -p.y * p.z / rho);
+
+        let normal = self.frame.world_normal(Normal::from_vec3(local_normal));
+        let p_error = ray_point_error(ray, t);
+        let mut hit_info = HitInfo::new(true, t, ray.at(t), p_error, normal, self.material.clone());
+        hit_info.set_face_normal(ray.direction, normal);
+        hit_info.set_uv(u, v);
+        hit_info.set_tangents(
+            self.frame.world_direction(Direction::from_vec3(dpdu)),
+            self.frame.world_direction(Direction::from_vec3(dpdv)),
+        );
+
+        Some(hit_info)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let e = self.major_radius + self.minor_radius;
+        Some(self.frame.world_bounds(
+            Vec3::new(-e, -self.minor_radius, -e),
+            Vec3::new(e, self.minor_radius, e),
+        ))
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::{Float, Vec3};
+
+    use crate::raytracer::{
+        geometry::{Direction, Point},
+        hit_info::Hittable,
+        material::MaterialKind,
+        radiance::Rgb,
+        ray::Ray,
+        torus::Torus,
+    };
+
+    #[test]
+    fn test_torus_hit() {
+        let material = MaterialKind::Lambertian {
+            albedo: Rgb::white(),
+        };
+        let torus = Torus::new(Point::ZERO, Vec3::Y, 1., 0.25, material);
+
+        // straight down through the tube
+        let ray = Ray::new(Point::new(1., 5., 0.), Direction::new(0., -1., 0.));
+        let hit = torus.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!((hit.t - 4.75).abs() < 1e-3);
+        assert!((*hit.normal - Vec3::Y).length() < 1e-3);
+        assert!(hit.dpdu.dot(Vec3::Y).abs() < 1e-3);
+        assert!(hit.dpdv.dot(Vec3::Y).abs() < 1e-3);
+        assert!(hit.dpdu.cross(hit.dpdv).length() > 1e-3);
+
+        // through the hole
+        let ray = Ray::new(Point::new(0., 5., 0.), Direction::new(0., -1., 0.));
+        assert!(torus.hit(&ray, 0.001, Float::INFINITY).is_none());
+
+        // along the equator, entering the outer rim first
+        let ray = Ray::new(Point::new(-5., 0., 0.), Direction::new(1., 0., 0.));
+        let hit = torus.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!((hit.t - 3.75).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,355 @@
+use std::{fs, io, path::Path};
+
+use quad_rand::RandomRange;
+
+use super::{
+    aabb::Aabb,
+    geometry::{Normal, Point},
+    hit_info::{HitInfo, Hittable},
+    material::MaterialKind,
+    math::{Float, Vec3},
+    radiance::Rgb,
+    ray::Ray,
+};
+
+/// Regular 3D grid of density values, looked up with trilinear interpolation.
+#[derive(Debug, Clone)]
+pub struct DensityGrid {
+    pub nx: usize,
+    pub ny: usize,
+    pub nz: usize,
+    data: Vec<Float>,
+    max_density: Float,
+}
+
+impl DensityGrid {
+    /// `data` is laid out x-fastest: `data[(z * ny + y) * nx + x]`.
+    pub fn new(nx: usize, ny: usize, nz: usize, data: Vec<Float>) -> io::Result<Self> {
+        if data.len() != voxel_count(nx, ny, nz)? {
+            return Err(invalid_data("grid size does not match data"));
+        }
+        let max_density = data.iter().copied().fold(0., Float::max);
+
+        Ok(Self {
+            nx,
+            ny,
+            nz,
+            data,
+            max_density,
+        })
+    }
+
+    /// Reads a headerless grid of little-endian f32 values.
+    pub fn load_raw(path: impl AsRef<Path>, nx: usize, ny: usize, nz: usize) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        if Some(bytes.len()) != voxel_count(nx, ny, nz)?.checked_mul(4) {
+            return Err(invalid_data("raw grid size does not match dimensions"));
+        }
+
+        Self::new(nx, ny, nz, read_f32s(&bytes))
+    }
+
+    /// Reads a Mitsuba-style `.vol` file (version 3, float32, one channel),
+    /// returning the grid and the bounding box stored in the header.
+    pub fn load_vol(path: impl AsRef<Path>) -> io::Result<(Self, Aabb)> {
+        Self::parse_vol(&fs::read(path)?)
+    }
+
+    pub fn parse_vol(bytes: &[u8]) -> io::Result<(Self, Aabb)> {
+        const HEADER: usize = 48;
+        if bytes.len() < HEADER || &bytes[0..3] != b"VOL" || bytes[3] != 3 {
+            return Err(invalid_data("not a version 3 .vol file"));
+        }
+
+        let header = read_i32s(&bytes[4..24]);
+        let (encoding, nx, ny, nz, channels) =
+            (header[0], header[1], header[2], header[3], header[4]);
+        if encoding != 1 || channels != 1 {
+            return Err(invalid_data(
+                "only single channel float32 .vol files are supported",
+            ));
+        }
+        if nx < 0 || ny < 0 || nz < 0 {
+            return Err(invalid_data("invalid .vol dimensions"));
+        }
+        let (nx, ny, nz) = (nx as usize, ny as usize, nz as usize);
+        let size = voxel_count(nx, ny, nz)?.checked_mul(4);
+
+        let bbox = read_f32s(&bytes[24..HEADER]);
+        let bounds = Aabb::new(
+            Vec3::new(bbox[0], bbox[1], bbox[2]),
+            Vec3::new(bbox[3], bbox[4], bbox[5]),
+        );
+
+        let data = &bytes[HEADER..];
+        if Some(data.len()) != size {
+            return Err(invalid_data(".vol data size does not match dimensions"));
+        }
+
+        Ok((Self::new(nx, ny, nz, read_f32s(data))?, bounds))
+    }
+
+    pub fn max_density(&self) -> Float {
+        self.max_density
+    }
+
+    fn voxel(&self, x: usize, y: usize, z: usize) -> Float {
+        self.data[(z * self.ny + y) * self.nx + x]
+    }
+
+    /// Density at normalized grid coordinates in `[0, 1]^3`.
+    pub fn lookup(&self, uvw: Vec3) -> Float {
+        // voxel values sit at cell centers
+        let g = uvw * Vec3::new(self.nx as Float, self.ny as Float, self.nz as Float) - 0.5;
+        let max = Vec3::new(
+            (self.nx - 1) as Float,
+            (self.ny - 1) as Float,
+            (self.nz - 1) as Float,
+        );
+        let g = g.clamp(Vec3::ZERO, max);
+        let i = g.floor();
+        let f = g - i;
+
+        let x0 = i.x as usize;
+        let y0 = i.y as usize;
+        let z0 = i.z as usize;
+        let x1 = (x0 + 1).min(self.nx - 1);
+        let y1 = (y0 + 1).min(self.ny - 1);
+        let z1 = (z0 + 1).min(self.nz - 1);
+
+        let lerp = |a: Float, b: Float, t: Float| a + (b - a) * t;
+        let c00 = lerp(self.voxel(x0, y0, z0), self.voxel(x1, y0, z0), f.x);
+        let c10 = lerp(self.voxel(x0, y1, z0), self.voxel(x1, y1, z0), f.x);
+        let c01 = lerp(self.voxel(x0, y0, z1), self.voxel(x1, y0, z1), f.x);
+        let c11 = lerp(self.voxel(x0, y1, z1), self.voxel(x1, y1, z1), f.x);
+
+        lerp(lerp(c00, c10, f.y), lerp(c01, c11, f.y), f.z)
+    }
+}
+
+/// Number of voxels in a grid, rejecting empty grids and sizes that overflow.
+fn voxel_count(nx: usize, ny: usize, nz: usize) -> io::Result<usize> {
+    if nx == 0 || ny == 0 || nz == 0 {
+        return Err(invalid_data("grid dimensions must be positive"));
+    }
+    nx.checked_mul(ny)
+        .and_then(|n| n.checked_mul(nz))
+        .ok_or_else(|| invalid_data("grid dimensions are too large"))
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn read_f32s(bytes: &[u8]) -> Vec<Float> {
+    bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as Float)
+        .collect()
+}
+
+fn read_i32s(bytes: &[u8]) -> Vec<i32> {
+    bytes
+        .chunks_exact(4)
+        .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+/// Participating medium whose density comes from a `DensityGrid` stretched over `bounds`.
+/// Collisions are found with delta tracking against the grid's maximum density.
+pub struct HeterogeneousMedium {
+    pub grid: DensityGrid,
+    pub bounds: Aabb,
+    /// multiplies grid values to get the extinction coefficient
+    pub density_scale: Float,
+    pub albedo: Rgb,
+    /// Henyey-Greenstein asymmetry, from -1 (back scattering) to 1 (forward scattering)
+    pub g: Float,
+    /// radiance emitted per unit of normalized density, e.g. for fire
+    pub emission: Rgb,
+}
+
+impl HeterogeneousMedium {
+    pub fn new(grid: DensityGrid, bounds: Aabb, density_scale: Float, albedo: Rgb) -> Self {
+        Self {
+            grid,
+            bounds,
+            density_scale,
+            albedo,
+            g: 0.,
+            emission: Rgb::black(),
+        }
+    }
+
+    pub fn with_phase(mut self, g: Float) -> Self {
+        self.g = g;
+        self
+    }
+
+    pub fn with_emission(mut self, emission: Rgb) -> Self {
+        self.emission = emission;
+        self
+    }
+
+    fn majorant(&self) -> Float {
+        self.grid.max_density() * self.density_scale
+    }
+
+    /// Extinction coefficient at world position `p`.
+    pub fn density(&self, p: Point) -> Float {
+        let uvw = (*p - self.bounds.min) / (self.bounds.max - self.bounds.min);
+        self.grid.lookup(uvw) * self.density_scale
+    }
+}
+
+fn sample_free_flight(sigma: Float) -> Float {
+    -RandomRange::gen_range(Float::EPSILON, 1.).ln() / sigma
+}
+
+impl Hittable for HeterogeneousMedium {
+    fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitInfo> {
+        let (t0, t1) = self.bounds.clip(ray, t_min, t_max)?;
+        let majorant = self.majorant();
+        if majorant <= 0. {
+            return None;
+        }
+
+        // delta tracking: step through a homogeneous medium of majorant density and
+        // accept a collision as real with probability density / majorant
+        let length = ray.direction.length();
+        let mut t = t0;
+        loop {
+            t += sample_free_flight(majorant) / length;
+            if t >= t1 {
+                return None;
+            }
+
+            let p = ray.at(t);
+            let density = self.density(p);
+            if RandomRange::gen_range(0., 1.) < density / majorant {
+                let strength = density / majorant;
+                let material = MaterialKind::Medium {
+                    albedo: self.albedo,
+                    g: self.g,
+                    emission: self.emission * strength,
+                };
+                let mut hit_info = HitInfo::new(true, t, p, Vec3::ZERO, Normal::X, material);
+                hit_info.front_face = true;
+                return Some(hit_info);
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bounds)
+    }
+
+    fn transmittance(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<Float> {
+        // ratio tracking: weight by the chance each tentative collision is null
+        // instead of sampling it, which has less variance than delta tracking
+        let Some((t0, t1)) = self.bounds.clip(ray, t_min, t_max) else {
+            return Some(1.);
+        };
+        let majorant = self.majorant();
+        if majorant <= 0. {
+            return Some(1.);
+        }
+
+        let length = ray.direction.length();
+        let mut transmittance = 1.;
+        let mut t = t0;
+        loop {
+            t += sample_free_flight(majorant) / length;
+            if t >= t1 {
+                return Some(transmittance);
+            }
+            transmittance *= 1. - self.density(ray.at(t)) / majorant;
+        }
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::raytracer::math::{Float, Vec3};
+
+    use crate::raytracer::{
+        aabb::Aabb,
+        geometry::{Direction, Point},
+        hit_info::Hittable,
+        radiance::Rgb,
+        ray::Ray,
+        volume::{DensityGrid, HeterogeneousMedium},
+    };
+
+    #[test]
+    fn test_grid_trilinear_lookup() {
+        let grid = DensityGrid::new(2, 1, 1, vec![0., 1.]).unwrap();
+
+        assert_eq!(grid.lookup(Vec3::new(0., 0.5, 0.5)), 0.);
+        assert!((grid.lookup(Vec3::new(0.5, 0.5, 0.5)) - 0.5).abs() < 1e-6);
+        assert_eq!(grid.lookup(Vec3::new(1., 0.5, 0.5)), 1.);
+        assert_eq!(grid.max_density(), 1.);
+    }
+
+    #[test]
+    fn test_parse_vol() {
+        let mut bytes = b"VOL".to_vec();
+        bytes.push(3);
+        for v in [1i32, 2, 1, 1, 1] {
+            bytes.extend(v.to_le_bytes());
+        }
+        for v in [0f32, 0., 0., 1., 1., 1., 0.25, 0.75] {
+            bytes.extend(v.to_le_bytes());
+        }
+
+        let (grid, bounds) = DensityGrid::parse_vol(&bytes).unwrap();
+        assert_eq!((grid.nx, grid.ny, grid.nz), (2, 1, 1));
+        assert_eq!(bounds.max, Vec3::ONE);
+        assert_eq!(grid.max_density(), 0.75);
+
+        assert!(DensityGrid::parse_vol(b"VOX").is_err());
+    }
+
+    #[test]
+    fn test_corrupt_vol_header_is_an_error() {
+        for dims in [[i32::MAX, i32::MAX, i32::MAX], [0, 2, 2], [-1, 1, 1]] {
+            let mut bytes = b"VOL".to_vec();
+            bytes.push(3);
+            for v in [1, dims[0], dims[1], dims[2], 1] {
+                bytes.extend(v.to_le_bytes());
+            }
+            bytes.extend([0; 24]);
+            let error = DensityGrid::parse_vol(&bytes).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+
+        let error = DensityGrid::new(2, 0, 1, vec![]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(DensityGrid::new(usize::MAX, 2, 1, vec![]).is_err());
+        assert!(DensityGrid::new(2, 1, 1, vec![0.]).is_err());
+    }
+
+    #[test]
+    fn test_tracking_matches_beer_lambert() {
+        let grid = DensityGrid::new(2, 2, 2, vec![1.; 8]).unwrap();
+        let medium =
+            HeterogeneousMedium::new(grid, Aabb::new(Vec3::ZERO, Vec3::ONE), 2., Rgb::white());
+        // crosses the unit cube along x, so d = 1
+        let ray = Ray::new(Point::new(-1., 0.5, 0.5), Direction::new(1., 0., 0.));
+        let expected = (-2. as Float).exp();
+
+        let n = 20000;
+        let escaped = (0..n)
+            .filter(|_| medium.hit(&ray, 0., Float::INFINITY).is_none())
+            .count();
+        assert!((escaped as Float / n as Float - expected).abs() < 0.02);
+
+        let transmittance: Float = (0..n)
+            .map(|_| medium.transmittance(&ray, 0., Float::INFINITY).unwrap())
+            .sum();
+        assert!((transmittance / n as Float - expected).abs() < 0.02);
+    }
+}
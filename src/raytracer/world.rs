@@ -1,13 +1,15 @@
-#![allow(dead_code, unused_variables)]
-
-use macroquad::prelude::Color;
-
 use super::{
-    hit_info::{HitInfo, Hittable},
-    material::MaterialKind,
+    aabb::Aabb,
+    hit_info::{Crossings, HitInfo, Hittable},
+    math::Float,
     ray::Ray,
 };
 
+/// Cut-out surfaces a ray may pass through on a single object before the
+/// next surface is treated as opaque.
+const MAX_ALPHA_CROSSINGS: usize = 32;
+
+#[derive(Default)]
 pub struct World {
     objects: Vec<Box<dyn Hittable>>,
 }
@@ -21,25 +23,191 @@ impl World {
         self.objects.push(object);
     }
 
-    pub fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitInfo> {
+    /// Bounds of every object in the world, or `None` if the world is empty
+    /// or contains an unbounded object such as a `Plane`.
+    pub fn bounding_box(&self) -> Option<Aabb> {
+        let mut bounds: Option<Aabb> = None;
+        for object in &self.objects {
+            let b = object.bounding_box()?;
+            bounds = Some(match bounds {
+                Some(a) => Aabb::surrounding(a, b),
+                None => b,
+            });
+        }
+        bounds
+    }
+
+    pub fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitInfo> {
+        self.hit_object(ray, t_min, t_max).map(|(_, info)| info)
+    }
+
+    /// Closest hit together with the object that was hit.
+    pub fn hit_object(
+        &self,
+        ray: &Ray,
+        t_min: Float,
+        t_max: Float,
+    ) -> Option<(&dyn Hittable, HitInfo)> {
+        let mut closest = None;
         let mut closest_so_far = t_max;
-        let material = MaterialKind::Lambertian {
-            albedo: Color::new(0., 0., 0., 1.),
-        };
-        let mut hit_info =
-            HitInfo::new(false, 0., Default::default(), Default::default(), material);
 
         for object in &self.objects {
-            if let Some(info) = object.hit(ray, t_min, closest_so_far) {
+            if let Some(info) = Self::hit_opaque(object.as_ref(), ray, t_min, closest_so_far) {
                 closest_so_far = info.t;
-                hit_info = info;
+                closest = Some((object.as_ref(), info));
             }
         }
 
-        if hit_info.hit {
-            return Some(hit_info);
+        closest
+    }
+
+    /// True if a surface blocks the ray between `t_min` and `t_max`, e.g. for
+    /// shadow rays. Cut-out surfaces and media do not block; use `transmittance`
+    /// to account for the latter.
+    pub fn occluded(&self, ray: &Ray, t_min: Float, t_max: Float) -> bool {
+        self.objects.iter().any(|object| {
+            Self::hit_opaque(object.as_ref(), ray, t_min, t_max)
+                .is_some_and(|info| !info.material.is_volumetric())
+        })
+    }
+
+    /// Fraction of light that reaches `t_max` from `t_min` along the ray:
+    /// zero if a surface is in the way, otherwise the transmittance of the media crossed.
+    pub fn transmittance(&self, ray: &Ray, t_min: Float, t_max: Float) -> Float {
+        let mut transmittance = 1.;
+        for object in &self.objects {
+            match object.transmittance(ray, t_min, t_max) {
+                Some(t) => transmittance *= t,
+                None if Self::hit_opaque(object.as_ref(), ray, t_min, t_max).is_some() => {
+                    return 0.
+                }
+                None => {}
+            }
         }
+        transmittance
+    }
+
+    /// Closest hit on `object` that is not cut out by an alpha mask.
+    fn hit_opaque(object: &dyn Hittable, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitInfo> {
+        let mut crossings = Crossings::new(object, ray, t_min, t_max);
+        for info in crossings.by_ref().take(MAX_ALPHA_CROSSINGS) {
+            if !info.material.passes_through(&info) {
+                return Some(info);
+            }
+        }
+
+        // rather than report a miss, give up on the cut-outs
+        crossings.next()
+    }
+}
+
+// unit tests
+#[cfg(test)]
+mod tests {
+    use crate::raytracer::math::{Float, Vec3};
+
+    use crate::raytracer::{
+        aabb::Aabb,
+        alpha::AlphaMask,
+        color::Color,
+        constant_medium::ConstantMedium,
+        geometry::{Direction, Point},
+        hit_info::{HitInfo, Hittable},
+        material::MaterialKind,
+        quad::Quad,
+        radiance::Rgb,
+        ray::Ray,
+        sphere::Sphere,
+        texture::Texture,
+        world::World,
+    };
+
+    use super::MAX_ALPHA_CROSSINGS;
+
+    fn quad_at(z: Float, material: MaterialKind) -> Box<Quad> {
+        Box::new(Quad::new(
+            Point::new(-1., -1., z),
+            Vec3::new(2., 0., 0.),
+            Vec3::new(0., 2., 0.),
+            material,
+        ))
+    }
+
+    #[test]
+    fn test_alpha_mask_cuts_out_hits() {
+        let solid = MaterialKind::Lambertian {
+            albedo: Rgb::new(0.5, 0.5, 0.5),
+        };
+        let mask = AlphaMask::new(Texture::Solid(Color::new(1., 1., 1., 0.)));
+        let cut_out = solid.clone().with_alpha_mask(mask);
+
+        let mut world = World::new();
+        world.add(quad_at(-1., cut_out));
+        let ray = Ray::new(Point::ZERO, Direction::new(0., 0., -1.));
+        assert!(world.hit(&ray, 0.001, Float::INFINITY).is_none());
+        assert!(!world.occluded(&ray, 0.001, Float::INFINITY));
+
+        world.add(quad_at(-2., solid));
+        let hit = world.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        assert!((hit.t - 2.).abs() < 1e-5);
+        assert!(world.occluded(&ray, 0.001, Float::INFINITY));
+    }
+
+    /// Many parallel quads acting as a single object.
+    struct Layers(Vec<Quad>);
+
+    impl Hittable for Layers {
+        fn hit(&self, ray: &Ray, t_min: Float, t_max: Float) -> Option<HitInfo> {
+            let mut closest: Option<HitInfo> = None;
+            for layer in &self.0 {
+                let t_max = closest.as_ref().map_or(t_max, |info| info.t);
+                closest = layer.hit(ray, t_min, t_max).or(closest);
+            }
+            closest
+        }
+
+        fn bounding_box(&self) -> Option<Aabb> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_too_many_cut_outs_do_not_miss() {
+        let mask = AlphaMask::new(Texture::Solid(Color::new(1., 1., 1., 0.)));
+        let cut_out = MaterialKind::Lambertian {
+            albedo: Rgb::white(),
+        }
+        .with_alpha_mask(mask);
+        let layers = (0..2 * MAX_ALPHA_CROSSINGS)
+            .map(|i| *quad_at(-1. - i as Float * 0.1, cut_out.clone()))
+            .collect();
+
+        let mut world = World::new();
+        world.add(Box::new(Layers(layers)));
+        let ray = Ray::new(Point::ZERO, Direction::new(0., 0., -1.));
+        let hit = world.hit(&ray, 0.001, Float::INFINITY).unwrap();
+        let expected = 1. + MAX_ALPHA_CROSSINGS as Float * 0.1;
+        assert!((hit.t - expected).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_media_do_not_occlude() {
+        let boundary = Sphere::new(
+            Point::new(0., 0., -3.),
+            1.,
+            MaterialKind::Lambertian {
+                albedo: Rgb::white(),
+            },
+        );
+        let mut world = World::new();
+        world.add(Box::new(ConstantMedium::new(
+            Box::new(boundary),
+            1000.,
+            Rgb::white(),
+        )));
 
-        None
+        let ray = Ray::new(Point::ZERO, Direction::new(0., 0., -1.));
+        assert!(!world.occluded(&ray, 0.001, Float::INFINITY));
+        assert!(world.transmittance(&ray, 0.001, Float::INFINITY) < 1e-6);
     }
 }